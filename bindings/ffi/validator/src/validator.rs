@@ -1,11 +1,42 @@
 pub use oca_conductor::data_set::DataSet;
 pub use oca_conductor::data_set::JSONDataSet;
 pub use oca_conductor::errors::GenericError;
-pub use oca_conductor::validator::{ConstraintsConfig, Validator as ValidatorRaw};
+pub use oca_conductor::validator::{
+    ConstraintsConfig as ConstraintsConfigRaw, Severity,
+    ValidationErrorKind as ValidationErrorKindRaw, Validator as ValidatorRaw,
+};
 
 use oca_rs::controller::load_oca;
 use std::sync::RwLock;
 
+/// Validation policy, defaults are declared in UDL and match
+/// `ConstraintsConfigRaw::default()`.
+pub struct ConstraintsConfig {
+    pub fail_on_additional_attributes: bool,
+    pub fail_on_duplicate_headers: bool,
+    pub strict_types: bool,
+    pub whitespace_as_missing: bool,
+    pub report_empty_values: bool,
+    pub max_errors: Option<u64>,
+    pub case_insensitive_headers: bool,
+    pub allow_mapped_headers: bool,
+}
+
+impl From<ConstraintsConfig> for ConstraintsConfigRaw {
+    fn from(config: ConstraintsConfig) -> Self {
+        Self {
+            fail_on_additional_attributes: config.fail_on_additional_attributes,
+            fail_on_duplicate_headers: config.fail_on_duplicate_headers,
+            strict_types: config.strict_types,
+            whitespace_as_missing: config.whitespace_as_missing,
+            report_empty_values: config.report_empty_values,
+            max_errors: config.max_errors.map(|max| max as usize),
+            case_insensitive_headers: config.case_insensitive_headers,
+            allow_mapped_headers: config.allow_mapped_headers,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ValidationError {
     Other {
@@ -13,9 +44,161 @@ pub enum ValidationError {
         record: String,
         attribute_name: String,
         message: String,
+        severity: Severity,
+        kind: ValidationErrorKind,
+    },
+}
+
+/// Reason of validation error, values are passed as JSON strings.
+#[derive(Debug)]
+pub enum ValidationErrorKind {
+    MissingAttribute,
+    MissingValue,
+    EmptyValue,
+    UnknownAttribute,
+    DuplicateHeader {
+        header: String,
+    },
+    EmptyArray,
+    TypeMismatch {
+        expected_type: String,
+        value: String,
+    },
+    FormatMismatch {
+        pattern: String,
+        value: String,
+    },
+    InvalidDateTime {
+        pattern: String,
+        value: String,
+    },
+    InvalidEncoding {
+        encoding: String,
+        value: String,
+    },
+    OutOfRange {
+        range: String,
+        value: String,
+    },
+    TooManyDecimals {
+        decimals: u64,
+        value: String,
+    },
+    LengthOutOfRange {
+        range: String,
+        value: String,
+    },
+    InvalidFormat {
+        pattern: String,
+    },
+    CardinalityMismatch {
+        cardinality: String,
+        count: u64,
+    },
+    InvalidCardinality {
+        cardinality: String,
+    },
+    RequiredByCondition {
+        condition: String,
+    },
+    InvalidCondition {
+        condition: String,
+    },
+    EntryCodeNotAllowed {
+        allowed_codes: Vec<String>,
+        value: String,
+    },
+    DeprecatedEntryCode {
+        value: String,
+    },
+    UnresolvedCodeList {
+        said: String,
+    },
+    UnresolvedReference {
+        said: String,
+    },
+    ParseError {
+        message: String,
     },
 }
 
+impl From<&ValidationErrorKindRaw> for ValidationErrorKind {
+    fn from(kind: &ValidationErrorKindRaw) -> Self {
+        match kind.clone() {
+            ValidationErrorKindRaw::MissingAttribute => Self::MissingAttribute,
+            ValidationErrorKindRaw::MissingValue => Self::MissingValue,
+            ValidationErrorKindRaw::EmptyValue => Self::EmptyValue,
+            ValidationErrorKindRaw::UnknownAttribute => Self::UnknownAttribute,
+            ValidationErrorKindRaw::DuplicateHeader { header } => Self::DuplicateHeader { header },
+            ValidationErrorKindRaw::EmptyArray => Self::EmptyArray,
+            ValidationErrorKindRaw::TypeMismatch {
+                expected_type,
+                value,
+            } => Self::TypeMismatch {
+                expected_type,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::FormatMismatch { pattern, value } => Self::FormatMismatch {
+                pattern,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::InvalidDateTime { pattern, value } => Self::InvalidDateTime {
+                pattern,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::InvalidEncoding { encoding, value } => Self::InvalidEncoding {
+                encoding,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::OutOfRange { range, value } => Self::OutOfRange {
+                range,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::TooManyDecimals { decimals, value } => Self::TooManyDecimals {
+                decimals: decimals as u64,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::LengthOutOfRange { range, value } => Self::LengthOutOfRange {
+                range,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::InvalidFormat { pattern } => Self::InvalidFormat { pattern },
+            ValidationErrorKindRaw::CardinalityMismatch { cardinality, count } => {
+                Self::CardinalityMismatch {
+                    cardinality,
+                    count: count as u64,
+                }
+            }
+            ValidationErrorKindRaw::InvalidCardinality { cardinality } => {
+                Self::InvalidCardinality { cardinality }
+            }
+            ValidationErrorKindRaw::RequiredByCondition { condition } => {
+                Self::RequiredByCondition { condition }
+            }
+            ValidationErrorKindRaw::InvalidCondition { condition } => {
+                Self::InvalidCondition { condition }
+            }
+            ValidationErrorKindRaw::EntryCodeNotAllowed {
+                allowed_codes,
+                value,
+            } => Self::EntryCodeNotAllowed {
+                allowed_codes,
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::DeprecatedEntryCode { value } => Self::DeprecatedEntryCode {
+                value: value.to_string(),
+            },
+            ValidationErrorKindRaw::UnresolvedCodeList { said } => {
+                Self::UnresolvedCodeList { said }
+            }
+            ValidationErrorKindRaw::UnresolvedReference { said } => {
+                Self::UnresolvedReference { said }
+            }
+            ValidationErrorKindRaw::ParseError { message } => Self::ParseError { message },
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ValidationErrors {
     List { errors: Vec<ValidationError> },
@@ -30,6 +213,21 @@ impl std::fmt::Display for ValidationErrors {
     }
 }
 
+/// OCA passed as JSON cannot be loaded.
+#[derive(Debug)]
+pub enum OcaError {
+    Invalid { message: String },
+}
+
+impl std::error::Error for OcaError {}
+impl std::fmt::Display for OcaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &*self {
+            OcaError::Invalid { message } => write!(f, "Invalid OCA: {}", message),
+        }
+    }
+}
+
 impl std::error::Error for ValidationError {}
 impl std::fmt::Display for ValidationError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -39,6 +237,7 @@ impl std::fmt::Display for ValidationError {
                 record,
                 attribute_name,
                 message,
+                ..
             } => write!(
                 f,
                 "Data Set: {}, Record {}: '{}' {}",
@@ -62,32 +261,60 @@ impl Validator {
     }
 
     pub fn set_constraints(&self, config: ConstraintsConfig) {
-        self.base.write().unwrap().set_constraints(config);
+        self.base.write().unwrap().set_constraints(config.into());
+    }
+
+    pub fn add_reference(&self, said: String, oca: String) -> Result<(), OcaError> {
+        let oca = load_oca(&mut oca.as_bytes())
+            .map_err(|e| OcaError::Invalid {
+                message: e.to_string(),
+            })?
+            .finalize();
+        self.base.write().unwrap().add_reference(&said, oca);
+        Ok(())
+    }
+
+    pub fn add_code_list(&self, said: String, codes: Vec<String>) {
+        self.base.write().unwrap().add_code_list(&said, codes);
+    }
+
+    pub fn set_language(&self, language: String) {
+        self.base.write().unwrap().set_language(language.as_str());
     }
 
+    pub fn add_deprecated_entry_codes(&self, attribute_name: String, codes: Vec<String>) {
+        self.base
+            .write()
+            .unwrap()
+            .add_deprecated_entry_codes(&attribute_name, codes);
+    }
+
+    /// Throws when any finding has `Error` severity. Thrown list holds
+    /// warnings as well.
     pub fn validate(&self, record: String) -> Result<(), ValidationErrors> {
-        let record_val: serde_json::Value = serde_json::from_str(&record).unwrap();
-        self.base.write().unwrap().add_data_set(JSONDataSet::new(
-            serde_json::to_string(&record_val).unwrap(),
-        ));
-        let r = self.base.read().unwrap().validate();
-
-        match r {
-            Ok(_) => Ok(()),
-            Err(errors) => {
-                return Err(ValidationErrors::List {
-                    errors: errors
-                        .iter()
-                        .map(|e| ValidationError::Other {
-                            data_set: e.data_set.clone(),
-                            record: e.record.clone(),
-                            attribute_name: e.attribute_name.clone(),
-                            message: e.message.clone(),
-                        })
-                        .collect(),
-                });
-            }
+        let r = self
+            .base
+            .read()
+            .unwrap()
+            .validate_data_set(JSONDataSet::new(record).as_ref());
+
+        if r.success {
+            return Ok(());
         }
+        Err(ValidationErrors::List {
+            errors: r
+                .errors
+                .iter()
+                .map(|e| ValidationError::Other {
+                    data_set: e.data_set.clone(),
+                    record: e.record.clone(),
+                    attribute_name: e.attribute_name.clone(),
+                    message: e.message.clone(),
+                    severity: e.severity,
+                    kind: ValidationErrorKind::from(&e.kind),
+                })
+                .collect(),
+        })
     }
 }
 
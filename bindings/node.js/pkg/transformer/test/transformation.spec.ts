@@ -1,5 +1,5 @@
 import { expect } from "chai"
-import { resolveFromZip, Transformer, CSVDataSet } from ".."
+import { resolveFromZip, Transformer, CSVDataSet, NDJSONDataSet } from ".."
 
 describe("Transformer", () => {
   describe("#addDataSet()", () => {
@@ -37,6 +37,42 @@ test@example.com,["a"]`, ','
       expect(result[0]).to.be.eq('email*,licenses*\ntest@example.com,["A"]')
     })
 
+    it("should transform NDJSON data set line by line", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const transformer = new Transformer(oca)
+      transformer.addDataSet(
+        new NDJSONDataSet(
+`{"e-mail*":"test@example.com","licenses*":["a"]}
+{"e-mail*":"other@example.com","licenses*":["b"]}`
+        ), [
+        `
+{
+  "attribute_mapping":{
+    "email*":"e-mail*"
+  },
+  "capture_base":"Et7SxuRi_lK6blZmUO3X80Ji5lqMJe7DucrbUmhyzUzk",
+  "digest":"Em51us0v3CuoYDZqxj4zB37w3lZHRjRyDa7TS9SJOJ7Q",
+  "type":"spec/overlays/mapping/1.0"
+}
+        `,
+        `
+{
+  "attribute_entry_codes_mapping":{
+    "licenses*":["a:A", "b:B", "c:C", "d:D", "e:E"]
+  },
+  "capture_base":"Et7SxuRi_lK6blZmUO3X80Ji5lqMJe7DucrbUmhyzUzk",
+  "digest":"EATJjWP-8p01ZHcQX2xB52VmaiMkiwVMG-VfBLyCs9So",
+  "type":"spec/overlays/entry_code_mapping/1.0"
+}
+        `
+      ])
+      const result = transformer.getRawDatasets()
+      expect(result.length).to.be.eq(1)
+      expect(result[0]).to.be.eq(
+        '{"email*":"test@example.com","licenses*":["A"]}\n{"email*":"other@example.com","licenses*":["B"]}'
+      )
+    })
+
     it("should throw errors when data_set is invalid", () => {
       const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
       const transformer = new Transformer(oca)
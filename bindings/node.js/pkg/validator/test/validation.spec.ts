@@ -1,5 +1,11 @@
 import { expect } from "chai"
-import { resolveFromZip, Validator, CSVDataSet } from ".."
+import {
+  resolveFromZip,
+  resolveReferencesFromZip,
+  resolveCodeListsFromZip,
+  Validator,
+  CSVDataSet
+} from ".."
 
 describe("Validator", () => {
   describe("constructor", () => {
@@ -43,6 +49,68 @@ describe("Validator", () => {
       expect(result.success).to.be.false
     })
 
+    it("should not report errors of previously validated records", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+
+      expect(validator.validate({ number: 'text' }).success).to.be.false
+      expect(
+        validator.validate({ 'email*': 'test@example.com', 'licenses*': ["A"] }).success
+      ).to.be.true
+    })
+
+    it("should return localised errors when language is set", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+      validator.setLanguage("de")
+
+      const result = validator.validate({
+        'licenses*': ["A"]
+      })
+
+      expect(result.success).to.be.false
+      expect(result.errors['email*']).to.eq('email* ist erforderlich')
+    })
+
+    it("should validate references against referenced OCA", () => {
+      const path = `${__dirname}/../../../../../assets/oca_bundle_with_references.zip`
+      const validator = new Validator(resolveFromZip(path))
+      const references = resolveReferencesFromZip(path)
+      for (const said in references) {
+        validator.addReference(said, references[said])
+      }
+      const codeLists = resolveCodeListsFromZip(path)
+      for (const said in codeLists) {
+        validator.addCodeList(said, codeLists[said])
+      }
+
+      const result = validator.validate({
+        code: 'glucose',
+        subject: { birth_date: '01.01.1990' }
+      })
+
+      expect(result.success).to.be.false
+      expect(result.errors).to.have.property('subject.name')
+    })
+
+    it("should return structured error kinds when record is invalid", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+
+      const result = validator.validate({
+        'email*': 'test@example.com',
+        'licenses*': ["A"],
+        number: 'text'
+      })
+
+      expect(result.success).to.be.false
+      expect(result.errorKinds.number).to.deep.eq({
+        kind: 'type_mismatch',
+        expected_type: 'Numeric',
+        value: 'text'
+      })
+    })
+
     it("should return unsuccessful validation result when data set has invalid records", () => {
       const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
       const validator = new Validator(oca)
@@ -82,5 +150,67 @@ describe("Validator", () => {
 
       expect(result.success).to.be.false
     })
+
+    it("should follow validation policy from constraints", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+      validator.setConstraints({
+        strictTypes: true,
+        caseInsensitiveHeaders: true,
+      })
+
+      const result = validator.validate({
+        'EMAIL*': 'test@example.com',
+        'licenses*': ["A"],
+        number: '24'
+      })
+
+      expect(result.success).to.be.false
+      expect(result.errors).to.have.all.keys('number')
+    })
+
+    it("should return warnings of successful validation result when record has additional attribute", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+
+      const result = validator.validate({
+        'email*': 'test@example.com',
+        'licenses*': ["A"],
+        additional: 'attribute'
+      })
+
+      expect(result.success).to.be.true
+      expect(result.errors).to.be.undefined
+      expect(result.warnings).to.have.property('additional')
+      expect(result.warningCount).to.be.eq(1)
+    })
+  })
+
+  describe("#coerce()", () => {
+    it("should return records normalised to attribute types", () => {
+      const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+      const validator = new Validator(oca)
+
+      const results = validator.coerce([{
+        'email*': ' test@example.com ',
+        'licenses*': ["A"],
+        numbers: [22, "23"],
+        bool: 'yes'
+      }, {
+        'email*': 'test@example.com',
+        number: 'text'
+      }])
+
+      expect(results.length).to.be.eq(2)
+      expect(results[0].success).to.be.true
+      expect(results[0].record).to.be.deep.eq({
+        'email*': 'test@example.com',
+        'licenses*': ["A"],
+        numbers: [22, 23],
+        bool: true
+      })
+      expect(results[1].success).to.be.false
+      expect(results[1].errors).to.have.property('number')
+    })
   })
 })
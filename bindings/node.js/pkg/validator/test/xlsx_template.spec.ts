@@ -0,0 +1,17 @@
+import { expect } from "chai"
+import { resolveFromZip, generateXlsxTemplate } from ".."
+
+describe("generateXlsxTemplate()", () => {
+  it("should return XLSX workbook for OCA bundle", () => {
+    const oca = resolveFromZip(`${__dirname}/../../../../../assets/oca_bundle.zip`)
+    const template = generateXlsxTemplate(oca, "en")
+
+    expect(template.subarray(0, 2).toString()).to.eq("PK")
+  })
+
+  it("should throw error when OCA object is invalid", () => {
+    expect(
+      () => generateXlsxTemplate({}, "en")
+    ).to.throw()
+  })
+})
@@ -2,6 +2,7 @@
 
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
+use oca_rs::state::oca::OCA;
 
 #[cfg(feature = "transformer")]
 pub mod transformer;
@@ -17,8 +18,36 @@ pub fn resolve_from_zip(env: Env, path: String) -> Result<napi::JsObject> {
     }
 }
 
+#[napi(ts_return_type = "Record<string, object>")]
+pub fn resolve_references_from_zip(env: Env, path: String) -> Result<napi::JsObject> {
+    let resolved = oca_zip_resolver::resolve_bundle_from_zip(path.as_str());
+    match resolved {
+        Ok(bundle) => Ok(env.to_js_value(&bundle.references)?.coerce_to_object()?),
+        Err(e) => Err(Error::from_reason(e)),
+    }
+}
+
+#[napi(ts_return_type = "Record<string, string[]>")]
+pub fn resolve_code_lists_from_zip(env: Env, path: String) -> Result<napi::JsObject> {
+    let resolved = oca_zip_resolver::resolve_bundle_from_zip(path.as_str());
+    match resolved {
+        Ok(bundle) => Ok(env.to_js_value(&bundle.code_lists)?.coerce_to_object()?),
+        Err(e) => Err(Error::from_reason(e)),
+    }
+}
+
+#[napi]
+pub fn generate_xlsx_template(env: Env, oca: napi::JsObject, language: String) -> Result<Buffer> {
+    let oca = env.from_js_value::<OCA, napi::JsObject>(oca)?;
+    match oca_conductor::xlsx_template::generate_xlsx_template(&oca, language.as_str()) {
+        Ok(template) => Ok(template.into()),
+        Err(e) => Err(Error::from_reason(e.to_string())),
+    }
+}
+
 pub enum DataSetType {
     CSVDataSet,
+    NDJSONDataSet,
 }
 
 #[napi]
@@ -45,3 +74,18 @@ impl CSVDataSetWrapper {
         }
     }
 }
+
+#[napi(js_name = "NDJSONDataSet")]
+pub struct NDJSONDataSetWrapper;
+
+#[napi]
+impl NDJSONDataSetWrapper {
+    #[napi(constructor)]
+    pub fn new(data_set: String) -> DataSetLoaderRouter {
+        DataSetLoaderRouter {
+            t: DataSetType::NDJSONDataSet,
+            raw: data_set,
+            delimiter: None
+        }
+    }
+}
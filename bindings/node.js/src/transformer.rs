@@ -3,7 +3,7 @@
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use crate::{DataSetType, DataSetLoaderRouter};
-use oca_conductor::data_set::{DataSet, CSVDataSet};
+use oca_conductor::data_set::{DataSet, CSVDataSet, NDJSONDataSet};
 use oca_conductor::Transformer;
 use oca_rs::state::oca::OCA;
 
@@ -23,10 +23,10 @@ impl TransformerWrapper {
     #[napi]
     pub fn add_data_set(
         &mut self,
-        #[napi(ts_arg_type = "CSVDataSet")] data_set: &DataSetLoaderRouter,
+        #[napi(ts_arg_type = "CSVDataSet | NDJSONDataSet")] data_set: &DataSetLoaderRouter,
         overlays: Option<Vec<&str>>
     ) -> Result<&Self> {
-        match data_set.t {
+        let internal_data_set: Box<dyn DataSet + Sync + Send> = match data_set.t {
             DataSetType::CSVDataSet => {
                 let mut internal_data_set = CSVDataSet::new(data_set.raw.clone());
                 if let Some(delimiter) = &data_set.delimiter {
@@ -34,15 +34,17 @@ impl TransformerWrapper {
                         internal_data_set.delimiter(*sign);
                     }
                 }
-                match self.base.add_data_set(internal_data_set, overlays) {
-                    Ok(_) => Ok(self),
-                    Err(errors) => Err(
-                        napi::Error::from_reason(
-                            errors.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(",")
-                        )
-                    )
-                }
+                internal_data_set
             }
+            DataSetType::NDJSONDataSet => NDJSONDataSet::new(data_set.raw.clone()),
+        };
+        match self.base.add_data_set(internal_data_set, overlays) {
+            Ok(_) => Ok(self),
+            Err(errors) => Err(
+                napi::Error::from_reason(
+                    errors.iter().map(|e| e.to_string()).collect::<Vec<String>>().join(",")
+                )
+            )
         }
     }
 
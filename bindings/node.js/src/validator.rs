@@ -3,14 +3,13 @@ use napi_derive::napi;
 
 use oca_conductor::data_set::{DataSet, JSONDataSet};
 use oca_conductor::validator::ConstraintsConfig;
-use oca_conductor::validator::ValidationError;
+use oca_conductor::validator::{Severity, ValidationError};
 use oca_conductor::Validator;
 use oca_rs::state::oca::OCA;
 
 #[napi(js_name = "Validator")]
 pub struct ValidatorWrapper {
     base: Validator,
-    many: bool
 }
 
 #[napi]
@@ -18,7 +17,7 @@ impl ValidatorWrapper {
     #[napi(constructor)]
     pub fn new(env: Env, oca: napi::JsObject) -> Result<Self> {
         let base = Validator::new(env.from_js_value::<OCA, napi::JsObject>(oca)?);
-        Ok(Self { base, many: false })
+        Ok(Self { base })
     }
 
     #[napi]
@@ -30,82 +29,191 @@ impl ValidatorWrapper {
         self
     }
 
+    #[napi]
+    pub fn add_reference(&mut self, env: Env, said: String, oca: napi::JsObject) -> Result<()> {
+        self.base.add_reference(&said, env.from_js_value::<OCA, napi::JsObject>(oca)?);
+        Ok(())
+    }
+
+    #[napi]
+    pub fn add_code_list(&mut self, said: String, codes: Vec<String>) -> &Self {
+        self.base.add_code_list(&said, codes);
+        self
+    }
+
+    #[napi]
+    pub fn add_deprecated_entry_codes(&mut self, attribute_name: String, codes: Vec<String>) -> &Self {
+        self.base.add_deprecated_entry_codes(&attribute_name, codes);
+        self
+    }
+
+    #[napi]
+    pub fn set_language(&mut self, language: String) -> &Self {
+        self.base.set_language(language.as_str());
+        self
+    }
+
     #[napi]
     pub fn validate(
-        &mut self,
+        &self,
         env: Env,
         #[napi(ts_arg_type = "object | object[]")] record: Object
     ) -> ValidationResult {
         let record_val = env.from_js_value::<serde_json::Value, napi::JsObject>(record).unwrap();
-        if record_val.is_array() {
-            self.many = true
-        }
-        self.base.add_data_set(JSONDataSet::new(
-            serde_json::to_string(&record_val).unwrap()
-        ));
-        ValidationResult::init(self.base.validate(), self.many)
+        let result = if record_val.is_array() {
+            self.base.validate_data_set(
+                JSONDataSet::new(serde_json::to_string(&record_val).unwrap()).as_ref()
+            )
+        } else {
+            self.base.validate_record(&record_val)
+        };
+        ValidationResult::init(&result, record_val.is_array())
     }
 
     #[napi]
-    pub fn get_raw_datasets(&self) -> Vec<String> {
-        match self.base.validate() {
-            Ok(_) => self.base.data_sets.iter().map(|ds| ds.get_raw()).collect(),
-            Err(errors) => errors.iter().map(|e| e.to_string()).collect()
-        }
+    pub fn coerce(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "object | object[]")] records: Object
+    ) -> Result<Vec<CoercionResult>> {
+        let records = match env.from_js_value::<serde_json::Value, napi::JsObject>(records)? {
+            serde_json::Value::Array(records) => records,
+            record => vec![record],
+        };
+        Ok(records
+            .iter()
+            .map(|record| {
+                let coerced = self.base.coerce_record(record);
+                let result = oca_conductor::ValidationResult::from(coerced.errors);
+                let ValidationResult { success, errors, error_kinds, warnings, .. } =
+                    ValidationResult::init(&result, false);
+                CoercionResult {
+                    record: coerced.record,
+                    success,
+                    errors,
+                    error_kinds,
+                    warnings,
+                }
+            })
+            .collect())
     }
 }
 
+/// Validation policy, omitted fields take defaults of `ConstraintsConfig`.
 #[napi(object, js_name = "ConstraintsConfig")]
 pub struct ConstraintsConfigWrapper {
-    pub fail_on_additional_attributes: bool,
+    pub fail_on_additional_attributes: Option<bool>,
+    pub fail_on_duplicate_headers: Option<bool>,
+    pub strict_types: Option<bool>,
+    pub whitespace_as_missing: Option<bool>,
+    pub report_empty_values: Option<bool>,
+    pub max_errors: Option<u32>,
+    pub case_insensitive_headers: Option<bool>,
+    pub allow_mapped_headers: Option<bool>,
 }
 
 impl ConstraintsConfigWrapper {
     fn to_base(&self) -> ConstraintsConfig {
+        let default = ConstraintsConfig::default();
         ConstraintsConfig {
-            fail_on_additional_attributes: self.fail_on_additional_attributes,
+            fail_on_additional_attributes: self
+                .fail_on_additional_attributes
+                .unwrap_or(default.fail_on_additional_attributes),
+            fail_on_duplicate_headers: self
+                .fail_on_duplicate_headers
+                .unwrap_or(default.fail_on_duplicate_headers),
+            strict_types: self.strict_types.unwrap_or(default.strict_types),
+            whitespace_as_missing: self
+                .whitespace_as_missing
+                .unwrap_or(default.whitespace_as_missing),
+            report_empty_values: self
+                .report_empty_values
+                .unwrap_or(default.report_empty_values),
+            max_errors: self.max_errors.map(|max| max as usize).or(default.max_errors),
+            case_insensitive_headers: self
+                .case_insensitive_headers
+                .unwrap_or(default.case_insensitive_headers),
+            allow_mapped_headers: self
+                .allow_mapped_headers
+                .unwrap_or(default.allow_mapped_headers),
         }
     }
 }
 
+/// Record normalised to types of its attributes with validation result of
+/// the normalised record.
+#[napi(object)]
+pub struct CoercionResult {
+    pub record: serde_json::Value,
+    pub success: bool,
+    pub errors: Option<serde_json::Value>,
+    pub error_kinds: Option<serde_json::Value>,
+    pub warnings: Option<serde_json::Value>,
+}
+
+/// Errors and error kinds hold findings of `error` severity only, the
+/// non-blocking ones are given as messages in warnings.
 #[napi(object)]
 pub struct ValidationResult {
     pub success: bool,
     pub errors: Option<serde_json::Value>,
+    pub error_kinds: Option<serde_json::Value>,
+    pub warnings: Option<serde_json::Value>,
+    pub error_count: u32,
+    pub warning_count: u32,
+    pub info_count: u32,
 }
 
 impl ValidationResult {
-    fn init(result: std::result::Result<(), Vec<ValidationError>>, many_records: bool) -> Self {
-        match result {
-            Ok(_) => Self {
-                success: true,
-                errors: None,
-            },
-            Err(errors) => Self {
-                success: false,
-                errors: Some(Self::format_errors(errors, many_records))
-            }
+    fn init(result: &oca_conductor::ValidationResult, many_records: bool) -> Self {
+        let errors = result
+            .with_severity(Severity::Error)
+            .collect::<Vec<_>>();
+        let warnings = result
+            .errors
+            .iter()
+            .filter(|e| e.severity != Severity::Error)
+            .collect::<Vec<_>>();
+        let messages = |e: &ValidationError| serde_json::Value::String(e.message.clone());
+        Self {
+            success: result.success,
+            errors: (!errors.is_empty())
+                .then(|| Self::format_errors(&errors, many_records, messages)),
+            error_kinds: (!errors.is_empty()).then(|| {
+                Self::format_errors(&errors, many_records, |e| {
+                    serde_json::to_value(&e.kind).unwrap_or(serde_json::Value::Null)
+                })
+            }),
+            warnings: (!warnings.is_empty())
+                .then(|| Self::format_errors(&warnings, many_records, messages)),
+            error_count: result.error_count as u32,
+            warning_count: result.warning_count as u32,
+            info_count: result.info_count as u32,
         }
     }
 
-    fn format_errors(errors: Vec<ValidationError>, many_records: bool) -> serde_json::Value {
+    fn format_errors(
+        errors: &[&ValidationError],
+        many_records: bool,
+        error_value: impl Fn(&ValidationError) -> serde_json::Value
+    ) -> serde_json::Value {
         let mut result_map: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
-        for error in errors {
+        for &error in errors {
             if many_records {
                 match result_map.get_mut(&error.record) {
                     Some(record_errors) => {
                         if let serde_json::Value::Object(record_errors_map) = record_errors {
-                            record_errors_map.insert(error.attribute_name, serde_json::Value::String(error.message));
+                            record_errors_map.insert(error.attribute_name.clone(), error_value(error));
                         }
                     },
                     None => {
                         let mut record_errors_map = serde_json::Map::new();
-                        record_errors_map.insert(error.attribute_name, serde_json::Value::String(error.message));
-                        result_map.insert(error.record, serde_json::Value::Object(record_errors_map));
+                        record_errors_map.insert(error.attribute_name.clone(), error_value(error));
+                        result_map.insert(error.record.clone(), serde_json::Value::Object(record_errors_map));
                     }
                 }
             } else {
-                result_map.insert(error.attribute_name, serde_json::Value::String(error.message));
+                result_map.insert(error.attribute_name.clone(), error_value(error));
             }
         }
         serde_json::Value::Object(result_map)
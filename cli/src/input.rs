@@ -0,0 +1,137 @@
+use clap::ValueEnum;
+use oca_conductor::data_set::{CSVDataSet, DataSet, JSONDataSet, NDJSONDataSet};
+use oca_conductor::validator::ConstraintsConfig;
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum InputFormat {
+    Csv,
+    Json,
+    Ndjson,
+}
+
+impl InputFormat {
+    /// Detects format by file extension or, when it is not known, by content:
+    /// a single JSON document is JSON, other input starting with `{` is
+    /// NDJSON and anything else is CSV.
+    pub fn detect(path: &str, content: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase());
+        match extension.as_deref() {
+            Some("csv" | "tsv") => return Self::Csv,
+            Some("json") => return Self::Json,
+            Some("ndjson" | "jsonl") => return Self::Ndjson,
+            _ => (),
+        }
+        match content.trim_start().chars().next() {
+            Some('[') => Self::Json,
+            Some('{') if serde_json::from_str::<Value>(content).is_ok() => Self::Json,
+            Some('{') => Self::Ndjson,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// Reads file or standard input when path is `-`.
+pub fn read(path: &str) -> Result<String, String> {
+    let mut content = String::new();
+    if path == "-" {
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Error while reading standard input. {e}"))?;
+    } else {
+        content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Error while loading {path} file. {e}"))?;
+    }
+    Ok(content)
+}
+
+pub fn data_set(
+    content: String,
+    format: InputFormat,
+    delimiter: Option<char>,
+) -> Box<dyn DataSet + Sync + Send> {
+    match format {
+        InputFormat::Csv => {
+            let delimiter = delimiter.unwrap_or_else(|| detect_delimiter(&content));
+            let mut data_set = CSVDataSet::new(content);
+            data_set.delimiter(delimiter);
+            data_set
+        }
+        InputFormat::Json => JSONDataSet::new(content),
+        InputFormat::Ndjson => NDJSONDataSet::new(content),
+    }
+}
+
+/// Picks the most frequent of `,`, `;`, tab and `|` in the header row.
+/// Defaults to `;`, as `CSVDataSet` does.
+fn detect_delimiter(content: &str) -> char {
+    let header = content.lines().next().unwrap_or_default();
+    [';', ',', '\t', '|']
+        .into_iter()
+        .map(|c| (c, header.matches(c).count()))
+        .fold((';', 0), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Reads validation policy from JSON file, omitted fields take their
+/// defaults.
+pub fn constraints(path: &str) -> Result<ConstraintsConfig, String> {
+    serde_json::from_str(&read(path)?)
+        .map_err(|e| format!("{path} is not a valid constraints config ({e})"))
+}
+
+/// Reads overlays from file holding a JSON array of overlays or a single
+/// overlay.
+pub fn overlays(path: &str) -> Result<Vec<String>, String> {
+    parse_overlays(path, &read(path)?)
+}
+
+/// Splits JSON array of overlays or a single overlay into overlays. `name`
+/// refers to the input in error messages.
+pub fn parse_overlays(name: &str, content: &str) -> Result<Vec<String>, String> {
+    match serde_json::from_str::<Value>(content)
+        .map_err(|e| format!("{name} is not valid JSON ({e})"))?
+    {
+        Value::Array(overlays) => Ok(overlays.iter().map(|o| o.to_string()).collect()),
+        overlay @ Value::Object(_) => Ok(vec![overlay.to_string()]),
+        _ => Err(format!(
+            "{name} must hold an overlay or an array of overlays"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_format_by_extension_and_content() {
+        assert_eq!(InputFormat::detect("data.CSV", "{}"), InputFormat::Csv);
+        assert_eq!(InputFormat::detect("data.jsonl", "[]"), InputFormat::Ndjson);
+        assert_eq!(InputFormat::detect("-", " [{\"a\": 1}]"), InputFormat::Json);
+        assert_eq!(InputFormat::detect("-", "{\"a\": 1}\n"), InputFormat::Json);
+        assert_eq!(
+            InputFormat::detect("-", "{\"a\": 1}\n{\"a\": 2}\n"),
+            InputFormat::Ndjson
+        );
+        assert_eq!(InputFormat::detect("data", "a;b\n1;2"), InputFormat::Csv);
+    }
+
+    #[test]
+    fn detect_delimiter_from_header_row() {
+        assert_eq!(detect_delimiter("a,b,c\n1;2;3;4"), ',');
+        assert_eq!(detect_delimiter("a\tb"), '\t');
+        assert_eq!(detect_delimiter("a"), ';');
+    }
+}
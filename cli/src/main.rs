@@ -0,0 +1,285 @@
+mod input;
+mod report;
+#[cfg(feature = "server")]
+mod server;
+
+use clap::{Args, Parser, Subcommand};
+use input::InputFormat;
+use oca_conductor::{Transformer, Validator};
+use oca_zip_resolver::{resolve_bundle_from_zip, OCABundle};
+use report::ReportFormat;
+use std::io::Write;
+use std::process::ExitCode;
+
+/// Validates and transforms data sets with OCA Bundles.
+///
+/// Exits with code 0 on success, 1 when data sets are invalid or cannot be
+/// transformed and 2 when arguments or input files cannot be used.
+#[derive(Parser)]
+#[command(name = "oca-conductor", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validates data sets against OCA Bundle
+    Validate(ValidateArgs),
+    /// Transforms data set with transformation overlays
+    Transform(TransformArgs),
+    /// Serves `POST /validate`, `POST /transform` and `POST /bundles` HTTP
+    /// endpoints
+    #[cfg(feature = "server")]
+    Serve(ServeArgs),
+}
+
+#[derive(Args)]
+struct InputArgs {
+    /// OCA Bundle zip file
+    #[arg(short, long)]
+    bundle: String,
+    /// Delimiter of CSV data sets, detected from header row by default
+    #[arg(short, long)]
+    delimiter: Option<char>,
+    /// Format of data sets, detected from file extension or content by default
+    #[arg(long, value_enum)]
+    input_format: Option<InputFormat>,
+    /// Format of the report
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Text)]
+    format: ReportFormat,
+}
+
+#[derive(Args)]
+struct ValidateArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Language of error messages, i.e. `en` or `de`
+    #[arg(short, long)]
+    language: Option<String>,
+    /// Validation policy, JSON file with fields of `ConstraintsConfig`, i.e.
+    /// `{"strict_types": true, "max_errors": 100}`
+    #[arg(long)]
+    constraints: Option<String>,
+    /// Data set files, `-` for standard input
+    #[arg(required = true)]
+    data: Vec<String>,
+}
+
+#[derive(Args)]
+struct TransformArgs {
+    #[command(flatten)]
+    input: InputArgs,
+    /// Overlays describing data set in relation to the bundle, applied
+    /// before validation (JSON array of overlays or a single overlay)
+    #[arg(long)]
+    source_overlays: Option<String>,
+    /// Transformation overlays (JSON array of overlays or a single overlay)
+    #[arg(long)]
+    overlays: Option<String>,
+    /// Output file, standard output by default
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Data set file, `-` for standard input
+    data: String,
+}
+
+#[cfg(feature = "server")]
+#[derive(Args)]
+struct ServeArgs {
+    /// Address to listen on
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    address: String,
+    /// OCA Bundle zip files to cache on start, available by digest printed
+    /// for each of them
+    #[arg(short, long)]
+    bundle: Vec<String>,
+    /// Maximum number of uploaded bundles kept in memory, least recently
+    /// used ones are evicted first
+    #[arg(long, default_value_t = 100)]
+    max_bundles: usize,
+    /// Maximum size of request body in bytes
+    #[arg(long, default_value_t = 64 * 1024 * 1024)]
+    body_limit: usize,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Validate(args) => validate(args, &mut std::io::stdout()),
+        Command::Transform(args) => transform(args),
+        #[cfg(feature = "server")]
+        Command::Serve(args) => server::serve(
+            &args.address,
+            &args.bundle,
+            args.max_bundles,
+            args.body_limit,
+        )
+        .map(|_| true),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn load_data_set(
+    input: &InputArgs,
+    path: &str,
+) -> Result<Box<dyn oca_conductor::data_set::DataSet + Sync + Send>, String> {
+    let content = input::read(path)?;
+    let format = input
+        .input_format
+        .unwrap_or_else(|| InputFormat::detect(path, &content));
+    Ok(input::data_set(content, format, input.delimiter))
+}
+
+/// Returns whether all data sets are valid.
+fn validate(args: ValidateArgs, out: &mut impl Write) -> Result<bool, String> {
+    let OCABundle {
+        oca,
+        references,
+        code_lists,
+    } = resolve_bundle_from_zip(&args.input.bundle)?;
+    let mut validator = Validator::new(oca);
+    for (said, reference) in references {
+        validator.add_reference(&said, reference);
+    }
+    for (said, codes) in code_lists {
+        validator.add_code_list(&said, codes);
+    }
+    if let Some(language) = &args.language {
+        validator.set_language(language);
+    }
+    if let Some(path) = &args.constraints {
+        validator.set_constraints(input::constraints(path)?);
+    }
+    for path in &args.data {
+        validator.add_data_set(load_data_set(&args.input, path)?);
+    }
+
+    let result = validator.validate();
+    report::write_validation(out, args.input.format, &args.data, &result)
+        .map_err(|e| e.to_string())?;
+    Ok(result.success)
+}
+
+/// Returns whether data set was transformed. Transformed data set is written
+/// to output file or standard output, in which case the report goes to
+/// standard error.
+fn transform(args: TransformArgs) -> Result<bool, String> {
+    let OCABundle {
+        oca,
+        references,
+        code_lists,
+    } = resolve_bundle_from_zip(&args.input.bundle)?;
+    let source_overlays = args
+        .source_overlays
+        .as_deref()
+        .map(input::overlays)
+        .transpose()?;
+    let overlays = args
+        .overlays
+        .as_deref()
+        .map(input::overlays)
+        .transpose()?
+        .unwrap_or_default();
+    let data_set = load_data_set(&args.input, &args.data)?;
+
+    let mut transformer = Transformer::new(oca);
+    for (said, reference) in references {
+        transformer.add_reference(&said, reference);
+    }
+    for (said, codes) in code_lists {
+        transformer.add_code_list(&said, codes);
+    }
+    let result = transformer
+        .add_data_set(
+            data_set,
+            source_overlays
+                .as_ref()
+                .map(|o| o.iter().map(String::as_str).collect()),
+        )
+        .and_then(|transformer| {
+            transformer.transform(overlays.iter().map(String::as_str).collect())
+        })
+        .map(|transformer| transformer.get_raw_datasets().concat());
+
+    let errors = match result {
+        Ok(transformed) => {
+            match &args.output {
+                Some(path) => std::fs::write(path, transformed)
+                    .map_err(|e| format!("Error while writing {path} file. {e}"))?,
+                None => println!("{transformed}"),
+            }
+            vec![]
+        }
+        Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+    };
+    let report_out: &mut dyn Write = match args.output {
+        Some(_) => &mut std::io::stdout(),
+        None => &mut std::io::stderr(),
+    };
+    report::write_errors(report_out, args.input.format, &errors).map_err(|e| e.to_string())?;
+    Ok(errors.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(name: &str, data: &str, format: ReportFormat) -> ValidateArgs {
+        let path = std::env::temp_dir().join(format!("{name}-{}.csv", std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        ValidateArgs {
+            input: InputArgs {
+                bundle: format!("{}/../assets/oca_bundle.zip", env!("CARGO_MANIFEST_DIR")),
+                delimiter: None,
+                input_format: None,
+                format,
+            },
+            language: None,
+            constraints: None,
+            data: vec![path.to_string_lossy().to_string()],
+        }
+    }
+
+    #[test]
+    fn validate_csv_data_set() {
+        let mut out = vec![];
+        let valid = validate(
+            args(
+                "valid",
+                "email*,licenses*\ntest@example.com,[\"A\"]",
+                ReportFormat::Text,
+            ),
+            &mut out,
+        );
+
+        assert_eq!(valid, Ok(true));
+        assert_eq!(String::from_utf8(out).unwrap(), "All data sets are valid\n");
+    }
+
+    #[test]
+    fn report_validation_errors_as_json() {
+        let mut out = vec![];
+        let valid = validate(
+            args("invalid", "email*\ntest@example.com", ReportFormat::Json),
+            &mut out,
+        );
+
+        assert_eq!(valid, Ok(false));
+        let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(report["success"], false);
+        assert_eq!(report["errors"][0]["record"], "0");
+        assert_eq!(report["errors"][0]["attribute_name"], "licenses*");
+        assert!(report["errors"][0]["file"]
+            .as_str()
+            .unwrap()
+            .ends_with(".csv"));
+    }
+}
@@ -0,0 +1,123 @@
+use clap::ValueEnum;
+use oca_conductor::validator::{Severity, ValidationError};
+use oca_conductor::ValidationResult;
+use serde::Serialize;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Serialize)]
+struct Report<E: Serialize> {
+    success: bool,
+    errors: Vec<E>,
+}
+
+#[derive(Serialize)]
+struct ValidationReport<'a> {
+    success: bool,
+    error_count: usize,
+    warning_count: usize,
+    info_count: usize,
+    errors: Vec<FileError<'a>>,
+}
+
+/// Validation error with data set index replaced by path of the file it was
+/// loaded from.
+#[derive(Serialize)]
+struct FileError<'a> {
+    file: &'a str,
+    #[serde(flatten)]
+    error: &'a ValidationError,
+}
+
+pub fn write_validation(
+    out: &mut dyn Write,
+    format: ReportFormat,
+    files: &[String],
+    result: &ValidationResult,
+) -> io::Result<()> {
+    let file_errors = result
+        .errors
+        .iter()
+        .map(|error| FileError {
+            file: error
+                .data_set
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| files.get(i))
+                .map_or(error.data_set.as_str(), |file| file.as_str()),
+            error,
+        })
+        .collect::<Vec<_>>();
+    match format {
+        ReportFormat::Json => {
+            let report = ValidationReport {
+                success: result.success,
+                error_count: result.error_count,
+                warning_count: result.warning_count,
+                info_count: result.info_count,
+                errors: file_errors,
+            };
+            serde_json::to_writer_pretty(&mut *out, &report)?;
+            writeln!(out)
+        }
+        ReportFormat::Text => {
+            for FileError { file, error } in &file_errors {
+                let location = match error.record.as_str() {
+                    "" => file.to_string(),
+                    record => format!("{file}, record {record}"),
+                };
+                let severity = match error.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "info",
+                };
+                match error.attribute_name.as_str() {
+                    "" => writeln!(out, "{location}: {severity}: {}", error.message)?,
+                    attribute_name => writeln!(
+                        out,
+                        "{location}: {severity}: '{attribute_name}' {}",
+                        error.message
+                    )?,
+                }
+            }
+            match (result.success, result.errors.len()) {
+                (true, 0) => writeln!(out, "All data sets are valid"),
+                (true, _) => writeln!(
+                    out,
+                    "All data sets are valid, found {} warning(s) and {} info(s)",
+                    result.warning_count, result.info_count
+                ),
+                (false, _) => writeln!(
+                    out,
+                    "Found {} error(s), {} warning(s) and {} info(s)",
+                    result.error_count, result.warning_count, result.info_count
+                ),
+            }
+        }
+    }
+}
+
+pub fn write_errors(
+    out: &mut dyn Write,
+    format: ReportFormat,
+    errors: &[String],
+) -> io::Result<()> {
+    match format {
+        ReportFormat::Json => write_json(out, errors.to_vec()),
+        ReportFormat::Text => errors.iter().try_for_each(|e| writeln!(out, "{e}")),
+    }
+}
+
+fn write_json<E: Serialize>(out: &mut dyn Write, errors: Vec<E>) -> io::Result<()> {
+    let report = Report {
+        success: errors.is_empty(),
+        errors,
+    };
+    serde_json::to_writer_pretty(&mut *out, &report)?;
+    writeln!(out)
+}
@@ -0,0 +1,665 @@
+use crate::input::{self, InputFormat};
+use axum::body::Bytes;
+use axum::extract::{DefaultBodyLimit, Multipart, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use clap::ValueEnum;
+use oca_conductor::validator::{ValidationError, SUPPORTED_LOCALES};
+use oca_conductor::{Transformer, Validator};
+use oca_rs::state::oca::OCA;
+use oca_zip_resolver::{resolve_bundle_from_bytes, resolve_bundle_from_zip, OCABundle};
+use said::derivation::SelfAddressing;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// OCA Bundles resolved from zip files, keyed by digest of their content.
+/// Bundles passed on start are kept, uploaded ones are evicted least
+/// recently used first once there are more than `max_bundles` of them.
+pub struct BundleCache {
+    max_bundles: usize,
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    bundles: HashMap<String, CacheEntry>,
+    /// Digests of uploaded zip files mapped to ids of bundles resolved from
+    /// them, so each zip file is parsed once.
+    uploads: HashMap<String, String>,
+    /// Incremented on each use of a bundle.
+    clock: u64,
+}
+
+struct CacheEntry {
+    bundle: Arc<CachedBundle>,
+    last_used: u64,
+    pinned: bool,
+}
+
+/// OCAs are kept serialized, as they cannot be shared between threads.
+/// Validators are built from them once and cloned where needed, so only OCA
+/// of transformed bundle is deserialized again on each request.
+struct CachedBundle {
+    oca: String,
+    references: BTreeMap<String, String>,
+    code_lists: BTreeMap<String, Vec<String>>,
+    /// Validators prepared for the bundle, keyed by language of messages.
+    validators: Mutex<HashMap<Option<String>, Arc<Validator>>>,
+}
+
+impl BundleCache {
+    pub fn new(max_bundles: usize) -> Self {
+        Self {
+            max_bundles,
+            entries: Mutex::default(),
+        }
+    }
+
+    /// Adds bundle to the cache and returns id it is available under. Same
+    /// bundle added again is reused.
+    pub fn insert(&self, bundle: OCABundle) -> String {
+        self.add(bundle, true)
+    }
+
+    /// Returns id of bundle resolved from uploaded zip file, resolving and
+    /// caching it unless the same file was uploaded before.
+    fn upload(&self, zip: &[u8]) -> Result<String, String> {
+        let upload = digest(zip);
+        {
+            let mut entries = self.entries.lock().unwrap();
+            if let Some(id) = entries.uploads.get(&upload).cloned() {
+                if entries.touch(&id).is_some() {
+                    return Ok(id);
+                }
+            }
+        }
+        let id = self.add(resolve_bundle_from_bytes(zip, "bundle")?, false);
+        self.entries
+            .lock()
+            .unwrap()
+            .uploads
+            .insert(upload, id.clone());
+        Ok(id)
+    }
+
+    fn add(&self, bundle: OCABundle, pinned: bool) -> String {
+        let oca = serialize(&bundle.oca);
+        let references = bundle
+            .references
+            .iter()
+            .map(|(said, oca)| (said.clone(), serialize(oca)))
+            .collect::<BTreeMap<_, _>>();
+        let id = digest(
+            serde_json::to_string(&(&oca, &references, &bundle.code_lists))
+                .expect("bundle is serializable")
+                .as_bytes(),
+        );
+
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.bundles.get_mut(&id) {
+            entry.pinned |= pinned;
+        } else {
+            let bundle = CachedBundle {
+                oca,
+                references,
+                code_lists: bundle.code_lists,
+                validators: Mutex::default(),
+            };
+            entries.bundles.insert(
+                id.clone(),
+                CacheEntry {
+                    bundle: Arc::new(bundle),
+                    last_used: 0,
+                    pinned,
+                },
+            );
+        }
+        entries.touch(&id);
+        entries.evict(self.max_bundles, &id);
+        id
+    }
+
+    fn get(&self, id: &str) -> Option<Arc<CachedBundle>> {
+        self.entries.lock().unwrap().touch(id)
+    }
+}
+
+impl Entries {
+    /// Marks bundle as used and returns it.
+    fn touch(&mut self, id: &str) -> Option<Arc<CachedBundle>> {
+        self.clock += 1;
+        let entry = self.bundles.get_mut(id)?;
+        entry.last_used = self.clock;
+        Some(entry.bundle.clone())
+    }
+
+    /// Removes least recently used uploaded bundles above the limit, apart
+    /// from the one being added.
+    fn evict(&mut self, max_bundles: usize, added: &str) {
+        while self.bundles.values().filter(|e| !e.pinned).count() > max_bundles {
+            let Some(id) = self
+                .bundles
+                .iter()
+                .filter(|(id, e)| !e.pinned && *id != added)
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+            self.bundles.remove(&id);
+            self.uploads.retain(|_, bundle_id| *bundle_id != id);
+        }
+    }
+}
+
+impl CachedBundle {
+    fn oca(&self) -> OCA {
+        deserialize(&self.oca)
+    }
+
+    /// Returns validator of the bundle. Validators of `SUPPORTED_LOCALES` are
+    /// reused, other languages get a clone of the default one each time.
+    fn validator(&self, language: Option<&str>) -> Arc<Validator> {
+        let language = language.map(|l| l.to_lowercase());
+        let mut validators = self.validators.lock().unwrap();
+        if let Some(validator) = validators.get(&language) {
+            return validator.clone();
+        }
+        let default = validators
+            .entry(None)
+            .or_insert_with(|| Arc::new(self.new_validator()))
+            .clone();
+        let Some(language) = language else {
+            return default;
+        };
+        let mut validator = Validator::clone(&default);
+        validator.set_language(&language);
+        let validator = Arc::new(validator);
+        if SUPPORTED_LOCALES.contains(&language.as_str()) {
+            validators.insert(Some(language), validator.clone());
+        }
+        validator
+    }
+
+    fn new_validator(&self) -> Validator {
+        let mut validator = Validator::new(self.oca());
+        for (said, reference) in &self.references {
+            validator.add_reference(said, deserialize(reference));
+        }
+        for (said, codes) in &self.code_lists {
+            validator.add_code_list(said, codes.clone());
+        }
+        validator
+    }
+}
+
+fn serialize(oca: &OCA) -> String {
+    serde_json::to_string(oca).expect("OCA is serializable")
+}
+
+/// Returns Blake3 digest of the bytes in the form of SAIDs.
+fn digest(bytes: &[u8]) -> String {
+    SelfAddressing::Blake3_256.derive(bytes).to_string()
+}
+
+fn deserialize(oca: &str) -> OCA {
+    oca_rs::controller::load_oca(&mut oca.as_bytes())
+        .expect("OCA was serialized from valid OCA")
+        .finalize()
+}
+
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let report = Report::<String> {
+            bundle_id: None,
+            success: false,
+            errors: vec![self.message],
+            counts: None,
+            data: None,
+        };
+        (self.status, Json(report)).into_response()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report<E: Serialize> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bundle_id: Option<String>,
+    success: bool,
+    errors: Vec<E>,
+    /// Number of findings per severity of validation.
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    counts: Option<Counts>,
+    /// Transformed data set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Counts {
+    error_count: usize,
+    warning_count: usize,
+    info_count: usize,
+}
+
+/// Fields of `multipart/form-data` request. Bundle is given either as zip
+/// file (`bundle`) or id of cached bundle (`bundle_id`), which is digest of
+/// bundle content and not SAID of its capture base.
+#[derive(Default)]
+pub struct Form {
+    bundle: Option<Bytes>,
+    bundle_id: Option<String>,
+    data: Option<String>,
+    input_format: Option<String>,
+    delimiter: Option<String>,
+    language: Option<String>,
+    source_overlays: Option<String>,
+    overlays: Option<String>,
+}
+
+impl Form {
+    async fn read(mut multipart: Multipart) -> Result<Self, ApiError> {
+        let mut form = Self::default();
+        while let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| ApiError::bad_request(e.body_text()))?
+        {
+            let name = field.name().unwrap_or_default().to_string();
+            if name == "bundle" {
+                form.bundle = Some(
+                    field
+                        .bytes()
+                        .await
+                        .map_err(|e| ApiError::bad_request(e.body_text()))?,
+                );
+                continue;
+            }
+            let text = field
+                .text()
+                .await
+                .map_err(|e| ApiError::bad_request(e.body_text()))?;
+            let value = match name.as_str() {
+                "bundle_id" => &mut form.bundle_id,
+                "data" => &mut form.data,
+                "input_format" => &mut form.input_format,
+                "delimiter" => &mut form.delimiter,
+                "language" => &mut form.language,
+                "source_overlays" => &mut form.source_overlays,
+                "overlays" => &mut form.overlays,
+                _ => return Err(ApiError::bad_request(format!("Unknown field '{name}'"))),
+            };
+            *value = Some(text);
+        }
+        Ok(form)
+    }
+
+    /// Returns id of the bundle together with the bundle, resolving and
+    /// caching uploaded zip file.
+    fn bundle(&self, cache: &BundleCache) -> Result<(String, Arc<CachedBundle>), ApiError> {
+        let id = match (&self.bundle, &self.bundle_id) {
+            (Some(zip), _) => cache.upload(zip).map_err(ApiError::bad_request)?,
+            (None, Some(id)) => id.clone(),
+            (None, None) => return Err(ApiError::bad_request("Missing 'bundle' or 'bundle_id'")),
+        };
+        match cache.get(&id) {
+            Some(bundle) => Ok((id, bundle)),
+            None => Err(ApiError {
+                status: StatusCode::NOT_FOUND,
+                message: format!("Unknown bundle '{id}'"),
+            }),
+        }
+    }
+
+    fn data_set(
+        &self,
+    ) -> Result<Box<dyn oca_conductor::data_set::DataSet + Sync + Send>, ApiError> {
+        let data = self
+            .data
+            .clone()
+            .ok_or_else(|| ApiError::bad_request("Missing 'data'"))?;
+        let format = match &self.input_format {
+            Some(format) => InputFormat::from_str(format, true)
+                .map_err(|_| ApiError::bad_request(format!("Unknown input format '{format}'")))?,
+            None => InputFormat::detect("", &data),
+        };
+        let delimiter = match self
+            .delimiter
+            .as_deref()
+            .map(|d| d.chars().collect::<Vec<_>>())
+        {
+            Some(chars) if chars.len() == 1 => Some(chars[0]),
+            Some(_) => {
+                return Err(ApiError::bad_request(
+                    "Delimiter must be a single character",
+                ))
+            }
+            None => None,
+        };
+        Ok(input::data_set(data, format, delimiter))
+    }
+}
+
+/// Reads overlays given as JSON array of overlays or a single overlay.
+fn overlays(overlays: &Option<String>) -> Result<Option<Vec<String>>, ApiError> {
+    overlays
+        .as_deref()
+        .map(|o| input::parse_overlays("overlays", o).map_err(ApiError::bad_request))
+        .transpose()
+}
+
+pub fn validate(cache: &BundleCache, form: Form) -> Result<Report<ValidationError>, ApiError> {
+    let (id, bundle) = form.bundle(cache)?;
+    let data_set = form.data_set()?;
+    let result = bundle
+        .validator(form.language.as_deref())
+        .validate_data_set(data_set.as_ref());
+    Ok(Report {
+        bundle_id: Some(id),
+        success: result.success,
+        counts: Some(Counts {
+            error_count: result.error_count,
+            warning_count: result.warning_count,
+            info_count: result.info_count,
+        }),
+        errors: result.errors,
+        data: None,
+    })
+}
+
+pub fn transform(cache: &BundleCache, form: Form) -> Result<Report<String>, ApiError> {
+    let (id, bundle) = form.bundle(cache)?;
+    let data_set = form.data_set()?;
+    let source_overlays = overlays(&form.source_overlays)?;
+    let overlays = overlays(&form.overlays)?.unwrap_or_default();
+
+    let mut transformer =
+        Transformer::with_validator(bundle.oca(), Validator::clone(&bundle.validator(None)));
+    let result = transformer
+        .add_data_set(
+            data_set,
+            source_overlays
+                .as_ref()
+                .map(|o| o.iter().map(String::as_str).collect()),
+        )
+        .and_then(|transformer| {
+            transformer.transform(overlays.iter().map(String::as_str).collect())
+        })
+        .map(|transformer| transformer.get_raw_datasets().concat());
+    let (data, errors) = match result {
+        Ok(data) => (Some(data), vec![]),
+        Err(errors) => (None, errors.iter().map(|e| e.to_string()).collect()),
+    };
+    Ok(Report {
+        bundle_id: Some(id),
+        success: errors.is_empty(),
+        errors,
+        counts: None,
+        data,
+    })
+}
+
+#[derive(Serialize)]
+struct BundleId {
+    bundle_id: String,
+}
+
+async fn upload_bundle(
+    State(cache): State<Arc<BundleCache>>,
+    zip: Bytes,
+) -> Result<Json<BundleId>, ApiError> {
+    tokio::task::spawn_blocking(move || {
+        Ok(Json(BundleId {
+            bundle_id: cache.upload(&zip).map_err(ApiError::bad_request)?,
+        }))
+    })
+    .await
+    .map_err(|e| ApiError {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        message: e.to_string(),
+    })?
+}
+
+async fn handle<E: Serialize + Send + 'static>(
+    cache: Arc<BundleCache>,
+    multipart: Multipart,
+    handler: fn(&BundleCache, Form) -> Result<Report<E>, ApiError>,
+) -> Result<Json<Report<E>>, ApiError> {
+    let form = Form::read(multipart).await?;
+    tokio::task::spawn_blocking(move || handler(&cache, form).map(Json))
+        .await
+        .map_err(|e| ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: e.to_string(),
+        })?
+}
+
+pub fn router(cache: Arc<BundleCache>, body_limit: usize) -> Router {
+    Router::new()
+        .route("/bundles", post(upload_bundle))
+        .route(
+            "/validate",
+            post(|State(cache), multipart| handle(cache, multipart, validate)),
+        )
+        .route(
+            "/transform",
+            post(|State(cache), multipart| handle(cache, multipart, transform)),
+        )
+        .layer(DefaultBodyLimit::max(body_limit))
+        .with_state(cache)
+}
+
+/// Serves HTTP API until the process is stopped. Bundles given as paths are
+/// cached up front and kept, up to `max_bundles` uploaded ones besides them.
+pub fn serve(
+    address: &str,
+    bundles: &[String],
+    max_bundles: usize,
+    body_limit: usize,
+) -> Result<(), String> {
+    let cache = BundleCache::new(max_bundles);
+    for path in bundles {
+        let id = cache.insert(resolve_bundle_from_zip(path)?);
+        eprintln!("Loaded {path} as {id}");
+    }
+    let app = router(Arc::new(cache), body_limit);
+
+    tokio::runtime::Runtime::new()
+        .map_err(|e| e.to_string())?
+        .block_on(async {
+            let listener = tokio::net::TcpListener::bind(address).await?;
+            eprintln!("Listening on {}", listener.local_addr()?);
+            axum::serve(listener, app).await
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_cache() -> (BundleCache, String) {
+        let cache = BundleCache::new(10);
+        let path = format!("{}/../assets/oca_bundle.zip", env!("CARGO_MANIFEST_DIR"));
+        let said = cache.insert(resolve_bundle_from_zip(&path).unwrap());
+        (cache, said)
+    }
+
+    fn read_bundle() -> Vec<u8> {
+        std::fs::read(format!(
+            "{}/../assets/oca_bundle.zip",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn reuse_uploaded_bundles() {
+        let (cache, id) = setup_cache();
+        let zip = read_bundle();
+
+        assert_eq!(cache.upload(&zip).unwrap(), id);
+        assert_eq!(cache.upload(&zip).unwrap(), id);
+        let entries = cache.entries.lock().unwrap();
+        assert_eq!(entries.bundles.len(), 1);
+        assert_eq!(entries.uploads.len(), 1);
+        assert!(entries.bundles[&id].pinned);
+    }
+
+    #[test]
+    fn keep_bundles_of_same_capture_base_apart() {
+        let cache = BundleCache::new(10);
+        let zip = read_bundle();
+        let mut bundle = resolve_bundle_from_bytes(&zip, "bundle").unwrap();
+        bundle
+            .code_lists
+            .insert("E123".to_string(), vec!["A".to_string()]);
+
+        let id = cache.upload(&zip).unwrap();
+        let other_id = cache.add(bundle, false);
+        assert_ne!(id, other_id);
+        assert!(cache.get(&id).unwrap().code_lists.is_empty());
+        assert_eq!(cache.get(&other_id).unwrap().code_lists.len(), 1);
+    }
+
+    #[test]
+    fn evict_least_recently_used_uploaded_bundles() {
+        let cache = BundleCache::new(1);
+        let path = format!("{}/../assets/oca_bundle.zip", env!("CARGO_MANIFEST_DIR"));
+        let bundle = |code: &str| {
+            let mut bundle = resolve_bundle_from_zip(&path).unwrap();
+            bundle
+                .code_lists
+                .insert("E123".to_string(), vec![code.to_string()]);
+            bundle
+        };
+        let pinned_id = cache.insert(bundle("A"));
+
+        let id = cache.upload(&read_bundle()).unwrap();
+        let other_id = cache.add(bundle("B"), false);
+        assert!(cache.get(&id).is_none());
+        assert!(cache.get(&other_id).is_some());
+        assert!(cache.get(&pinned_id).is_some());
+        assert!(cache.entries.lock().unwrap().uploads.is_empty());
+    }
+
+    #[test]
+    fn validate_data_set_with_cached_bundle() {
+        let (cache, said) = setup_cache();
+        let form = || Form {
+            bundle_id: Some(said.clone()),
+            data: Some(r#"[{"email*": "test@example.com"}]"#.to_string()),
+            ..Default::default()
+        };
+
+        let report = validate(&cache, form()).unwrap();
+        assert!(!report.success);
+        assert_eq!(report.bundle_id, Some(said.clone()));
+        assert_eq!(report.errors[0].attribute_name, "licenses*");
+        assert!(Arc::ptr_eq(
+            &cache.get(&said).unwrap().validator(None),
+            &cache.get(&said).unwrap().validator(None)
+        ));
+        assert_eq!(validate(&cache, form()).unwrap().errors.len(), 1);
+    }
+
+    #[test]
+    fn validate_data_set_in_unsupported_language() {
+        let (cache, said) = setup_cache();
+        let form = Form {
+            bundle_id: Some(said.clone()),
+            data: Some(r#"[{"email*": "test@example.com"}]"#.to_string()),
+            language: Some("es".to_string()),
+            ..Default::default()
+        };
+
+        let report = validate(&cache, form).unwrap();
+        assert_eq!(report.errors[0].attribute_name, "licenses*");
+        let bundle = cache.get(&said).unwrap();
+        assert_eq!(
+            bundle.validators.lock().unwrap().keys().collect::<Vec<_>>(),
+            vec![&None]
+        );
+    }
+
+    #[test]
+    fn validate_with_unknown_bundle_is_not_found() {
+        let (cache, _) = setup_cache();
+        let form = Form {
+            bundle_id: Some("unknown".to_string()),
+            data: Some("{}".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            validate(&cache, form).unwrap_err().status,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[test]
+    fn transform_uploaded_bundle_data_set() {
+        let cache = BundleCache::new(10);
+        let form = Form {
+            bundle: Some(Bytes::from(read_bundle())),
+            data: Some("e-mail*;licenses*\ntest@example.com;[\"A\"]".to_string()),
+            source_overlays: Some(
+                r#"{
+  "attribute_mapping":{ "email*":"e-mail*" },
+  "capture_base":"Et7SxuRi_lK6blZmUO3X80Ji5lqMJe7DucrbUmhyzUzk",
+  "digest":"Em51us0v3CuoYDZqxj4zB37w3lZHRjRyDa7TS9SJOJ7Q",
+  "type":"spec/overlays/mapping/1.0"
+}"#
+                .to_string(),
+            ),
+            ..Default::default()
+        };
+
+        let report = transform(&cache, form).unwrap();
+        assert!(report.success, "{:?}", report.errors);
+        assert_eq!(
+            report.data.as_deref(),
+            Some("email*;licenses*\ntest@example.com;\"[\"\"A\"\"]\"")
+        );
+        assert!(cache.get(report.bundle_id.as_ref().unwrap()).is_some());
+    }
+
+    #[test]
+    fn transform_data_set_with_references() {
+        let cache = BundleCache::new(10);
+        let path = format!(
+            "{}/../assets/oca_bundle_with_references.zip",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let form = |birth_date: &str| Form {
+            bundle: Some(Bytes::from(std::fs::read(&path).unwrap())),
+            data: Some(format!(
+                r#"[{{ "code": "glucose", "subject": {{ "name": "John", "birth_date": "{birth_date}" }} }}]"#
+            )),
+            ..Default::default()
+        };
+
+        let report = transform(&cache, form("31.02.1990")).unwrap();
+        assert!(!report.success);
+        assert!(report.errors[0].contains("subject.birth_date"));
+        assert!(transform(&cache, form("28.02.1990")).unwrap().success);
+    }
+}
@@ -1,20 +1,20 @@
 use crate::data_set::DataSet;
 use crate::errors::GenericError;
 use serde::{Serialize, Serializer};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::BTreeMap;
 
 #[cfg(feature = "transformer")]
 use crate::transformer::data_set_transformer::Operation;
 #[cfg(feature = "transformer")]
 use oca_rs::state::oca::OCA;
-#[cfg(feature = "transformer")]
-use serde_json::Map;
 
 #[derive(Clone)]
 pub struct CSVDataSet {
     pub raw: String,
     delimiter: char,
+    quote: char,
+    trim: bool,
 }
 
 impl Serialize for CSVDataSet {
@@ -31,6 +31,8 @@ impl DataSet for CSVDataSet {
         Box::new(Self {
             raw,
             delimiter: ';',
+            quote: '"',
+            trim: false,
         })
     }
 
@@ -38,54 +40,29 @@ impl DataSet for CSVDataSet {
         self.raw.clone()
     }
 
+    fn headers(&self) -> Option<Vec<String>> {
+        self.reader()
+            .and_then(|mut reader| Self::header_row(&mut reader))
+            .ok()
+    }
+
     fn load(
         &self,
         attribute_types: BTreeMap<String, String>,
     ) -> Result<Vec<Value>, Vec<GenericError>> {
         let mut errors = vec![];
-        let mut rows_value = vec![];
-        for line in self.raw.lines() {
-            rows_value.push(Value::Array(
-                line.split(self.delimiter)
-                    .map(|el| Value::String(el.to_string()))
-                    .collect(),
-            ))
-        }
         let mut result = vec![];
 
-        let header_row_v = rows_value.get(0).unwrap().as_array().unwrap();
-        let header_row = header_row_v
-            .iter()
-            .map(|v| v.as_str().unwrap())
-            .collect::<Vec<&str>>();
+        let mut reader = self.reader().map_err(|e| vec![e])?;
+        let header_row = Self::header_row(&mut reader).map_err(|e| vec![e])?;
 
-        for row_value in &rows_value[1..rows_value.len()] {
-            if let Value::Array(row_v) = row_value {
-                let row: Vec<Value> = row_v
-                    .iter()
-                    .enumerate()
-                    .map(|(i, v)| {
-                        if let Value::String(v_str) = v {
-                            if v_str.trim().is_empty() {
-                                return Value::Null;
-                            }
-                        }
-                        let attribute_name = header_row.get(i).unwrap().to_string();
-                        let attribute_type_op = attribute_types.get(&attribute_name);
-                        match attribute_type_op {
-                            Some(attribute_type) => match Self::parse_value(v, attribute_type) {
-                                Ok(parsed) => parsed,
-                                Err(e) => {
-                                    errors
-                                        .push(GenericError::from(format!("{attribute_name}: {e}")));
-                                    Value::Null
-                                }
-                            },
-                            None => v.clone(),
-                        }
-                    })
-                    .collect();
-                result.push(header_row.iter().cloned().zip(row.clone()).collect());
+        for row_result in reader.records() {
+            match row_result
+                .map_err(|e| vec![GenericError::from(e)])
+                .and_then(|row| Self::parse_record(&header_row, &row, &attribute_types))
+            {
+                Ok(record) => result.push(record),
+                Err(e) => errors.extend(e),
             }
         }
         if !errors.is_empty() {
@@ -100,45 +77,47 @@ impl DataSet for CSVDataSet {
         mappings: BTreeMap<String, String>,
         subset_attributes_op: Option<Vec<String>>,
     ) -> Result<Box<dyn DataSet + Sync + Send>, GenericError> {
-        let mut transformed_raw = self.raw.clone();
-        if let Some(header_line) = transformed_raw.lines().take(1).next() {
-            let mut headers = header_line
-                .split(self.delimiter)
-                .map(|header| match mappings.get(header) {
-                    Some(mapping) => mapping,
-                    None => header,
-                })
-                .collect::<Vec<&str>>();
-            let mut records = transformed_raw
-                .lines()
-                .skip(1)
-                .map(|s| s.to_string())
-                .collect::<Vec<String>>();
-
-            if let Some(subset_attributes) = subset_attributes_op {
-                let keep: Vec<bool> = headers
-                    .clone()
-                    .iter()
-                    .map(|attr_name| subset_attributes.contains(&attr_name.to_string()))
-                    .collect();
-                let mut iter = keep.iter();
-                headers.retain(|_| *iter.next().unwrap());
-                records = records
+        let mut reader = self.reader()?;
+        let headers = reader
+            .headers()?
+            .iter()
+            .map(|header| match mappings.get(header) {
+                Some(mapping) => mapping.clone(),
+                None => header.to_string(),
+            })
+            .collect::<Vec<String>>();
+        let keep: Vec<bool> = headers
+            .iter()
+            .map(|attr_name| match &subset_attributes_op {
+                Some(subset_attributes) => subset_attributes.contains(attr_name),
+                None => true,
+            })
+            .collect();
+        let retain = |i: usize| *keep.get(i).unwrap_or(&subset_attributes_op.is_none());
+
+        let mut writer = self.writer()?;
+        writer.write_record(
+            headers
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| retain(*i))
+                .map(|(_, header)| header.as_str()),
+        )?;
+        for record in reader.records() {
+            let record = record?;
+            writer.write_record(
+                record
                     .iter()
-                    .map(|record| {
-                        let mut iter = keep.iter();
-                        let mut r = record.split(self.delimiter).collect::<Vec<&str>>();
-                        r.retain(|_| *iter.next().unwrap());
-                        r.join(&self.delimiter.to_string())
-                    })
-                    .collect::<Vec<String>>();
-            }
-            transformed_raw =
-                headers.join(&self.delimiter.to_string()) + "\n" + &records.join("\n");
+                    .enumerate()
+                    .filter(|(i, _)| retain(*i))
+                    .map(|(_, field)| field),
+            )?;
         }
-        Ok(Box::new(
-            Self::new(transformed_raw).delimiter(self.delimiter),
-        ))
+
+        Ok(Box::new(Self {
+            raw: Self::written(writer)?,
+            ..self.clone()
+        }))
     }
 
     #[cfg(feature = "transformer")]
@@ -197,35 +176,32 @@ impl DataSet for CSVDataSet {
             transformed_data_set.push(Value::Object(transformed_data));
         }
 
-        let mut data = String::new();
+        let mut writer = self.writer().map_err(|e| vec![e])?;
         for (i, record_val) in transformed_data_set.iter().enumerate() {
             if let Value::Object(record) = record_val {
                 if i == 0 {
-                    data.push_str(
-                        &record
-                            .keys()
-                            .map(|v| v.to_string())
-                            .collect::<Vec<String>>()
-                            .join(&self.delimiter.to_string()),
-                    );
+                    writer
+                        .write_record(record.keys())
+                        .map_err(|e| vec![GenericError::from(e)])?;
                 }
-                let line = String::from("\n")
-                    + &record
-                        .values()
-                        .map(|v| {
-                            if v.is_string() {
-                                v.as_str().unwrap().to_string()
-                            } else {
-                                v.to_string()
-                            }
-                        })
-                        .collect::<Vec<String>>()
-                        .join(&self.delimiter.to_string());
-                data.push_str(&line);
+                let values = record
+                    .values()
+                    .map(|v| match v {
+                        Value::String(v_str) => v_str.clone(),
+                        Value::Null => String::new(),
+                        _ => v.to_string(),
+                    })
+                    .collect::<Vec<String>>();
+                writer
+                    .write_record(&values)
+                    .map_err(|e| vec![GenericError::from(e)])?;
             }
         }
 
-        Ok(Box::new(Self::new(data).delimiter(self.delimiter)))
+        Ok(Box::new(Self {
+            raw: Self::written(writer).map_err(|e| vec![e])?,
+            ..self.clone()
+        }))
     }
 }
 
@@ -235,7 +211,132 @@ impl CSVDataSet {
         self.clone()
     }
 
-    fn parse_value(value: &Value, attribute_type: &str) -> Result<Value, GenericError> {
+    pub fn quote(&mut self, q: char) -> Self {
+        self.quote = q;
+        self.clone()
+    }
+
+    pub fn trim(&mut self, t: bool) -> Self {
+        self.trim = t;
+        self.clone()
+    }
+
+    fn reader(&self) -> Result<csv::Reader<&[u8]>, GenericError> {
+        Ok(Self::reader_builder(self.delimiter, self.quote, self.trim)?
+            .from_reader(self.raw.as_bytes()))
+    }
+
+    pub(crate) fn reader_builder(
+        delimiter: char,
+        quote: char,
+        trim: bool,
+    ) -> Result<csv::ReaderBuilder, GenericError> {
+        let (delimiter, quote) = Self::single_bytes(delimiter, quote)?;
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .delimiter(delimiter)
+            .quote(quote)
+            .trim(if trim {
+                csv::Trim::All
+            } else {
+                csv::Trim::None
+            })
+            .flexible(true);
+        Ok(builder)
+    }
+
+    fn single_bytes(delimiter: char, quote: char) -> Result<(u8, u8), GenericError> {
+        let delimiter = u8::try_from(delimiter)
+            .map_err(|_| GenericError::from("Delimiter must be a single byte character"))?;
+        let quote = u8::try_from(quote)
+            .map_err(|_| GenericError::from("Quote must be a single byte character"))?;
+        Ok((delimiter, quote))
+    }
+
+    /// Returns writer with delimiter and quote of the data set, quoting only
+    /// fields which would otherwise be read back differently.
+    #[cfg(feature = "transformer")]
+    fn writer(&self) -> Result<csv::Writer<Vec<u8>>, GenericError> {
+        let (delimiter, quote) = Self::single_bytes(self.delimiter, self.quote)?;
+        Ok(csv::WriterBuilder::new()
+            .delimiter(delimiter)
+            .quote(quote)
+            .quote_style(csv::QuoteStyle::Necessary)
+            .terminator(csv::Terminator::Any(b'\n'))
+            .flexible(true)
+            .from_writer(vec![]))
+    }
+
+    /// Returns lines written with `writer`, without trailing line break.
+    #[cfg(feature = "transformer")]
+    fn written(writer: csv::Writer<Vec<u8>>) -> Result<String, GenericError> {
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| GenericError::from(e.to_string()))?;
+        let mut raw = String::from_utf8(bytes)?;
+        if raw.ends_with('\n') {
+            raw.pop();
+        }
+        Ok(raw)
+    }
+
+    pub(crate) fn header_row<R: std::io::Read>(
+        reader: &mut csv::Reader<R>,
+    ) -> Result<Vec<String>, GenericError> {
+        Ok(reader
+            .headers()?
+            .iter()
+            .map(|header| header.to_string())
+            .collect())
+    }
+
+    /// Converts row into record with values parsed according to types of
+    /// attributes named in header row. Empty fields become `null`, fields
+    /// holding only whitespace are kept as text, so validator decides whether
+    /// they are missing.
+    pub(crate) fn parse_record(
+        header_row: &[String],
+        row: &csv::StringRecord,
+        attribute_types: &BTreeMap<String, String>,
+    ) -> Result<Value, Vec<GenericError>> {
+        if row.len() > header_row.len() {
+            return Err(vec![GenericError::from(format!(
+                "Record in line {} has {} fields, but header has {}",
+                row.position().map(|p| p.line()).unwrap_or_default(),
+                row.len(),
+                header_row.len()
+            ))]);
+        }
+
+        let mut errors = vec![];
+        let mut record = Map::new();
+        for (attribute_name, v_str) in header_row.iter().zip(row.iter()) {
+            let value = if v_str.is_empty() {
+                Value::Null
+            } else if v_str.trim().is_empty() {
+                Value::String(v_str.to_string())
+            } else {
+                let v = Value::String(v_str.to_string());
+                match attribute_types.get(attribute_name) {
+                    Some(attribute_type) => match Self::parse_value(&v, attribute_type) {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            errors.push(GenericError::from(format!("{attribute_name}: {e}")));
+                            Value::Null
+                        }
+                    },
+                    None => v,
+                }
+            };
+            record.insert(attribute_name.clone(), value);
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(Value::Object(record))
+    }
+
+    pub(crate) fn parse_value(value: &Value, attribute_type: &str) -> Result<Value, GenericError> {
         if value.is_string() {
             let value_str = value.as_str().unwrap();
             let parsed_value = match attribute_type {
@@ -349,6 +450,112 @@ test@example.com"#
         }
     }
 
+    #[test]
+    fn load_csv_data_set_with_quoted_fields() {
+        let oca = setup_oca();
+        let result = CSVDataSet::new(
+            "email*;licenses*;note\r\n\"a;b@example.com\";\"[\"\"A\"\"]\";\"first\nsecond\"\r\n"
+                .to_string(),
+        )
+        .load(oca.capture_base.attributes);
+
+        assert!(result.is_ok());
+        let records = result.unwrap();
+        assert_eq!(records.len(), 1);
+        let record = records.first().unwrap();
+        assert_eq!(
+            record["email*"],
+            Value::String("a;b@example.com".to_string())
+        );
+        assert_eq!(
+            record["licenses*"],
+            Value::Array(vec![Value::String("A".to_string())])
+        );
+        assert_eq!(record["note"], Value::String("first\nsecond".to_string()));
+    }
+
+    #[test]
+    fn load_csv_data_set_with_custom_quote_and_trim() {
+        let oca = setup_oca();
+        let result = CSVDataSet::new(
+            r#"email* ; number
+'a;b@example.com' ; 1 "#
+                .to_string(),
+        )
+        .quote('\'')
+        .trim(true)
+        .load(oca.capture_base.attributes);
+
+        assert!(result.is_ok());
+        let records = result.unwrap();
+        let record = records.first().unwrap();
+        assert_eq!(
+            record["email*"],
+            Value::String("a;b@example.com".to_string())
+        );
+        assert_eq!(record["number"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn load_csv_data_set_with_too_many_fields_is_err() {
+        let oca = setup_oca();
+        let result = CSVDataSet::new(
+            r#"email*
+a@example.com;1"#
+                .to_string(),
+        )
+        .load(oca.capture_base.attributes);
+
+        assert!(result.is_err());
+    }
+
+    #[cfg(feature = "transformer")]
+    #[test]
+    fn transform_schema_requotes_fields() {
+        let data_set = CSVDataSet::new(
+            "e-mail*;note;number\n\"a;b@example.com\";\"first\nsecond\";1".to_string(),
+        );
+        let result = data_set.transform_schema(
+            BTreeMap::from([("e-mail*".to_string(), "email*".to_string())]),
+            Some(vec!["email*".to_string(), "note".to_string()]),
+        );
+
+        assert!(result.is_ok());
+        let raw = result.unwrap().get_raw();
+        assert_eq!(raw, "email*;note\n\"a;b@example.com\";\"first\nsecond\"");
+
+        let oca = setup_oca();
+        let records = CSVDataSet::new(raw)
+            .load(oca.capture_base.attributes)
+            .unwrap();
+        assert_eq!(
+            records.first().unwrap()["note"],
+            Value::String("first\nsecond".to_string())
+        );
+    }
+
+    #[cfg(feature = "transformer")]
+    #[test]
+    fn transform_schema_quotes_fields_with_custom_quote() {
+        let data_set =
+            CSVDataSet::new("email*,licenses*\na@a.com,'[\"A\", \"it''s\"]'".to_string())
+                .delimiter(',')
+                .quote('\'');
+        let result = data_set.transform_schema(BTreeMap::new(), None).unwrap();
+
+        assert_eq!(
+            result.get_raw(),
+            "email*,licenses*\na@a.com,'[\"A\", \"it''s\"]'"
+        );
+        let oca = setup_oca();
+        let records = CSVDataSet::new(result.get_raw())
+            .delimiter(',')
+            .quote('\'')
+            .load(oca.capture_base.attributes)
+            .unwrap();
+        assert_eq!(records[0]["licenses*"], serde_json::json!(["A", "it's"]));
+    }
+
     #[test]
     fn parse_vaules_with_invalid_array() {
         let array_types = vec![
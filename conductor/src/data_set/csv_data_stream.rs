@@ -0,0 +1,166 @@
+use crate::data_set::{CSVDataSet, DataStream};
+use crate::errors::GenericError;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+/// CSV data set read row by row from `std::io::Read`. Values are parsed the
+/// same way as in `CSVDataSet`.
+pub struct CSVDataStream<R: Read> {
+    source: Option<R>,
+    reader: Option<csv::Reader<R>>,
+    header_row: Vec<String>,
+    /// Error of creating reader met by `headers`, returned by next call of
+    /// `next_record`.
+    error: Option<GenericError>,
+    delimiter: char,
+    quote: char,
+    trim: bool,
+}
+
+impl<R: Read> CSVDataStream<R> {
+    pub fn new(source: R) -> Self {
+        Self {
+            source: Some(source),
+            reader: None,
+            header_row: vec![],
+            error: None,
+            delimiter: ';',
+            quote: '"',
+            trim: false,
+        }
+    }
+
+    pub fn delimiter(mut self, d: char) -> Self {
+        self.delimiter = d;
+        self
+    }
+
+    pub fn quote(mut self, q: char) -> Self {
+        self.quote = q;
+        self
+    }
+
+    pub fn trim(mut self, t: bool) -> Self {
+        self.trim = t;
+        self
+    }
+
+    /// Creates reader and reads header row on first call. Returns error only
+    /// once, further calls end the stream.
+    fn reader(&mut self) -> Option<Result<&mut csv::Reader<R>, GenericError>> {
+        if let Some(source) = self.source.take() {
+            let mut reader = match CSVDataSet::reader_builder(self.delimiter, self.quote, self.trim)
+            {
+                Ok(builder) => builder.from_reader(source),
+                Err(e) => return Some(Err(e)),
+            };
+            match CSVDataSet::header_row(&mut reader) {
+                Ok(header_row) => self.header_row = header_row,
+                Err(e) => return Some(Err(e)),
+            }
+            self.reader = Some(reader);
+        }
+        self.reader.as_mut().map(Ok)
+    }
+}
+
+impl<R: Read> DataStream for CSVDataStream<R> {
+    fn next_record(
+        &mut self,
+        attribute_types: &BTreeMap<String, String>,
+    ) -> Option<Result<Value, Vec<GenericError>>> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(vec![e]));
+        }
+        let mut row = csv::StringRecord::new();
+        match self.reader()? {
+            Ok(reader) => match reader.read_record(&mut row) {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    if e.is_io_error() {
+                        self.reader = None;
+                    }
+                    return Some(Err(vec![GenericError::from(e)]));
+                }
+            },
+            Err(e) => return Some(Err(vec![e])),
+        }
+        Some(CSVDataSet::parse_record(
+            &self.header_row,
+            &row,
+            attribute_types,
+        ))
+    }
+
+    fn headers(&mut self) -> Option<Vec<String>> {
+        if let Err(e) = self.reader()? {
+            self.error = Some(e);
+            return None;
+        }
+        Some(self.header_row.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_csv_records_one_by_one() {
+        let attribute_types = BTreeMap::from([
+            ("name".to_string(), "Text".to_string()),
+            ("age".to_string(), "Numeric".to_string()),
+        ]);
+        let mut stream =
+            CSVDataStream::new("name,age\nAnna,30\nTom,x\n,".as_bytes()).delimiter(',');
+
+        assert_eq!(
+            stream.headers(),
+            Some(vec!["name".to_string(), "age".to_string()])
+        );
+        assert_eq!(
+            stream.next_record(&attribute_types).unwrap().unwrap(),
+            serde_json::json!({ "name": "Anna", "age": 30 })
+        );
+        let errors = stream.next_record(&attribute_types).unwrap().unwrap_err();
+        assert!(errors[0].to_string().starts_with("age: "));
+        assert_eq!(
+            stream.next_record(&attribute_types).unwrap().unwrap(),
+            serde_json::json!({ "name": null, "age": null })
+        );
+        assert!(stream.next_record(&attribute_types).is_none());
+    }
+
+    /// Source failing on every read after given data.
+    struct FailingSource(&'static [u8]);
+
+    impl Read for FailingSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::Error::other("connection reset"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn end_stream_after_read_error() {
+        let mut stream = CSVDataStream::new(FailingSource(b"a\n1\n")).delimiter(',');
+
+        assert!(stream.next_record(&BTreeMap::new()).unwrap().is_ok());
+        let errors = stream.next_record(&BTreeMap::new()).unwrap().unwrap_err();
+        assert!(errors[0].to_string().contains("connection reset"));
+        assert!(stream.next_record(&BTreeMap::new()).is_none());
+    }
+
+    #[test]
+    fn invalid_delimiter_ends_stream_with_error() {
+        let mut stream = CSVDataStream::new("a\n1".as_bytes()).delimiter('ż');
+
+        assert!(stream.headers().is_none());
+        assert!(stream.next_record(&BTreeMap::new()).unwrap().is_err());
+        assert!(stream.next_record(&BTreeMap::new()).is_none());
+    }
+}
@@ -38,21 +38,37 @@ impl DataSet for JSONDataSet {
         &self,
         _attribute_types: BTreeMap<String, String>,
     ) -> Result<Vec<Value>, Vec<GenericError>> {
-        match serde_json::from_str(&self.raw).unwrap_or(Value::Null) {
-            Value::Array(data_set_array) => Ok(data_set_array),
-            Value::Object(data_set_object) => Ok(vec![Value::Object(data_set_object)]),
-            _ => Ok(vec![]),
+        match serde_json::from_str(&self.raw) {
+            Ok(Value::Array(data_set_array)) => Ok(data_set_array),
+            Ok(Value::Object(data_set_object)) => Ok(vec![Value::Object(data_set_object)]),
+            Ok(_) => Err(vec![GenericError::from(
+                "Data set must be a JSON array or object",
+            )]),
+            Err(e) => Err(vec![GenericError::from(format!(
+                "Data set is not valid JSON ({e})"
+            ))]),
         }
     }
 
     #[cfg(feature = "transformer")]
     fn transform_schema(
         &self,
-        _mappings: BTreeMap<String, String>,
-        _subset_attributes_op: Option<Vec<String>>,
+        mappings: BTreeMap<String, String>,
+        subset_attributes_op: Option<Vec<String>>,
     ) -> Result<Box<dyn DataSet + Sync + Send>, GenericError> {
-        // TODO
-        Ok(Self::new(self.raw.clone()))
+        let transformed = match serde_json::from_str::<Value>(&self.raw)? {
+            Value::Array(records) => Value::Array(
+                records
+                    .into_iter()
+                    .map(|record| {
+                        Self::transform_record_schema(record, &mappings, &subset_attributes_op)
+                    })
+                    .collect(),
+            ),
+            record => Self::transform_record_schema(record, &mappings, &subset_attributes_op),
+        };
+
+        Ok(Self::new(serde_json::to_string(&transformed)?))
     }
 
     #[cfg(feature = "transformer")]
@@ -62,58 +78,191 @@ impl DataSet for JSONDataSet {
         entry_code_mappings: BTreeMap<String, BTreeMap<String, String>>,
         unit_transformation_operations: BTreeMap<String, Vec<Operation>>,
     ) -> Result<Box<dyn DataSet + Sync + Send>, Vec<GenericError>> {
-        // TODO
-        let mut transformed_data_set = vec![];
-
-        for record in self.load(oca.capture_base.attributes.clone())? {
-            let record_map = record.as_object().unwrap();
-            let mut transformed_data = Map::new();
-            for (k, v) in record_map {
-                let key = k.to_string();
-                let mut value = v.clone();
-                if let Some(mapped_entries) = entry_code_mappings.get(k) {
-                    match value {
-                        Value::Array(ref values_vec) => {
-                            let mut mapped_values = vec![];
-                            for v in values_vec {
-                                match mapped_entries.get(v.as_str().unwrap()) {
-                                    Some(mapped_entry) => {
-                                        mapped_values.push(Value::String(mapped_entry.to_string()));
-                                    }
-                                    None => {
-                                        mapped_values.push(v.clone());
-                                    }
+        let transformed_data_set = self
+            .load(oca.capture_base.attributes.clone())?
+            .iter()
+            .map(|record| {
+                Self::transform_record_data(
+                    self,
+                    record,
+                    &entry_code_mappings,
+                    &unit_transformation_operations,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self::new(
+            serde_json::to_string(&Value::Array(transformed_data_set)).unwrap(),
+        ))
+    }
+}
+
+impl JSONDataSet {
+    #[cfg(feature = "transformer")]
+    pub(crate) fn transform_record_schema(
+        record: Value,
+        mappings: &BTreeMap<String, String>,
+        subset_attributes_op: &Option<Vec<String>>,
+    ) -> Value {
+        match record {
+            Value::Object(record_map) => {
+                let mut transformed_record = Map::new();
+                for (k, v) in record_map {
+                    let key = match mappings.get(&k) {
+                        Some(mapping) => mapping.clone(),
+                        None => k,
+                    };
+                    if let Some(subset_attributes) = subset_attributes_op {
+                        if !subset_attributes.contains(&key) {
+                            continue;
+                        }
+                    }
+                    transformed_record.insert(key, v);
+                }
+                Value::Object(transformed_record)
+            }
+            _ => record,
+        }
+    }
+
+    /// Maps entry codes and converts units of record values.
+    #[cfg(feature = "transformer")]
+    pub(crate) fn transform_record_data(
+        data_set: &dyn DataSet,
+        record: &Value,
+        entry_code_mappings: &BTreeMap<String, BTreeMap<String, String>>,
+        unit_transformation_operations: &BTreeMap<String, Vec<Operation>>,
+    ) -> Value {
+        let Some(record_map) = record.as_object() else {
+            return record.clone();
+        };
+        let mut transformed_data = Map::new();
+        for (k, v) in record_map {
+            let key = k.to_string();
+            let mut value = v.clone();
+            if let Some(mapped_entries) = entry_code_mappings.get(k) {
+                match value {
+                    Value::Array(ref values_vec) => {
+                        let mut mapped_values = vec![];
+                        for v in values_vec {
+                            match mapped_entries.get(v.as_str().unwrap()) {
+                                Some(mapped_entry) => {
+                                    mapped_values.push(Value::String(mapped_entry.to_string()));
+                                }
+                                None => {
+                                    mapped_values.push(v.clone());
                                 }
                             }
-                            value = Value::Array(mapped_values);
                         }
-                        Value::String(_) => {
-                            if let Some(mapped_entry) = mapped_entries.get(value.as_str().unwrap())
-                            {
-                                value = Value::String(mapped_entry.to_string());
-                            };
-                        }
-                        _ => (),
+                        value = Value::Array(mapped_values);
                     }
-                }
-                if let Some(operations) = unit_transformation_operations.get(k) {
-                    if let Value::Number(num) = &value {
-                        value = Value::Number(
-                            serde_json::value::Number::from_f64(
-                                self.calculate_value_units(num.as_f64().unwrap(), operations),
-                            )
-                            .unwrap(),
-                        );
+                    Value::String(_) => {
+                        if let Some(mapped_entry) = mapped_entries.get(value.as_str().unwrap()) {
+                            value = Value::String(mapped_entry.to_string());
+                        };
                     }
+                    _ => (),
                 }
-                transformed_data.insert(key, value);
             }
+            if let Some(operations) = unit_transformation_operations.get(k) {
+                if let Value::Number(num) = &value {
+                    value = Value::Number(
+                        serde_json::value::Number::from_f64(
+                            data_set.calculate_value_units(num.as_f64().unwrap(), operations),
+                        )
+                        .unwrap(),
+                    );
+                }
+            }
+            transformed_data.insert(key, value);
+        }
+        Value::Object(transformed_data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_json_array_and_object() {
+        let data_set = JSONDataSet::new(r#"[{"email*":"a@a.com"},1]"#.to_string());
+        assert_eq!(data_set.load(BTreeMap::new()).unwrap().len(), 2);
+
+        let data_set = JSONDataSet::new(r#"{"email*":"a@a.com"}"#.to_string());
+        assert_eq!(data_set.load(BTreeMap::new()).unwrap().len(), 1);
+    }
 
-            transformed_data_set.push(Value::Object(transformed_data));
+    #[test]
+    fn load_malformed_json_reports_position() {
+        let data_set = JSONDataSet::new("[\n  {\"email*\": }\n]".to_string());
+        let errors = data_set.load(BTreeMap::new()).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "Data set is not valid JSON (expected value at line 2 column 14)"
+        );
+    }
+
+    #[test]
+    fn load_scalar_json_is_err() {
+        for raw in ["1", "\"text\"", "null"] {
+            let errors = JSONDataSet::new(raw.to_string())
+                .load(BTreeMap::new())
+                .unwrap_err();
+            assert_eq!(
+                errors[0].to_string(),
+                "Data set must be a JSON array or object"
+            );
         }
+    }
 
-        Ok(Self::new(
-            serde_json::to_string(&Value::Array(transformed_data_set)).unwrap(),
-        ))
+    #[test]
+    #[cfg(feature = "transformer")]
+    fn transform_schema_of_json_object() {
+        let data_set =
+            JSONDataSet::new(r#"{"e-mail*":"a@a.com","licenses*":["A"],"number":1}"#.to_string());
+        let result = data_set.transform_schema(
+            BTreeMap::from([("e-mail*".to_string(), "email*".to_string())]),
+            None,
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().get_raw(),
+            r#"{"email*":"a@a.com","licenses*":["A"],"number":1}"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "transformer")]
+    fn transform_schema_of_json_array_with_subset() {
+        let data_set = JSONDataSet::new(
+            r#"[
+  {"e-mail*":"a@a.com","licenses*":["A"],"number":1},
+  {"e-mail*":"b@b.com","licenses*":["B"],"subject":{"name":"John"}}
+]"#
+            .to_string(),
+        );
+        let result = data_set.transform_schema(
+            BTreeMap::from([("e-mail*".to_string(), "email*".to_string())]),
+            Some(vec!["email*".to_string(), "subject".to_string()]),
+        );
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().get_raw(),
+            r#"[{"email*":"a@a.com"},{"email*":"b@b.com","subject":{"name":"John"}}]"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "transformer")]
+    fn transform_schema_of_malformed_json_is_err() {
+        let data_set = JSONDataSet::new(r#"{"email*":"#.to_string());
+        let result = data_set.transform_schema(BTreeMap::new(), None);
+
+        assert!(result.is_err());
     }
 }
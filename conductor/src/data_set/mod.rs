@@ -1,15 +1,25 @@
 pub mod csv_data_set;
+pub mod csv_data_stream;
 pub mod json_data_set;
+pub mod ndjson_data_set;
+pub mod ndjson_data_stream;
+#[cfg(feature = "xlsx")]
+pub mod xlsx_data_set;
 
 use crate::errors::GenericError;
 #[cfg(feature = "transformer")]
 use crate::transformer::data_set_transformer::{OpType, Operation};
 pub use csv_data_set::CSVDataSet;
+pub use csv_data_stream::CSVDataStream;
 pub use json_data_set::JSONDataSet;
+pub use ndjson_data_set::NDJSONDataSet;
+pub use ndjson_data_stream::NDJSONDataStream;
 #[cfg(feature = "transformer")]
 use oca_rs::state::oca::OCA;
 use serde_json::Value;
 use std::collections::BTreeMap;
+#[cfg(feature = "xlsx")]
+pub use xlsx_data_set::XLSXDataSet;
 
 erased_serde::serialize_trait_object!(DataSet);
 dyn_clone::clone_trait_object!(DataSet);
@@ -22,6 +32,16 @@ pub trait DataSet: erased_serde::Serialize + dyn_clone::DynClone {
         &self,
         attribute_types: BTreeMap<String, String>,
     ) -> Result<Vec<Value>, Vec<GenericError>>;
+    /// Returns records with errors of records which could not be loaded in
+    /// their place, so the others are still validated. Errors of the whole
+    /// data set are returned as `Err`.
+    fn load_records(
+        &self,
+        attribute_types: BTreeMap<String, String>,
+    ) -> Result<Vec<Result<Value, Vec<GenericError>>>, Vec<GenericError>> {
+        self.load(attribute_types)
+            .map(|records| records.into_iter().map(Ok).collect())
+    }
     #[cfg(feature = "transformer")]
     fn transform_schema(
         &self,
@@ -38,6 +58,12 @@ pub trait DataSet: erased_serde::Serialize + dyn_clone::DynClone {
 
     fn get_raw(&self) -> String;
 
+    /// Returns headers of columns of tabular data sets, `None` when keys of
+    /// records are not declared up front.
+    fn headers(&self) -> Option<Vec<String>> {
+        None
+    }
+
     #[cfg(feature = "transformer")]
     fn calculate_value_units(&self, value: f64, operations: &[Operation]) -> f64 {
         let mut result = value;
@@ -58,3 +84,21 @@ pub trait DataSet: erased_serde::Serialize + dyn_clone::DynClone {
         }
     }
 }
+
+/// Data set read record by record, i.e. from `std::io::Read`, so whole input
+/// is never kept in memory.
+pub trait DataStream {
+    /// Returns next record with values parsed according to attribute types or
+    /// errors of the record. Returns `None` at the end of input and after
+    /// error of reading the input, which is returned once.
+    fn next_record(
+        &mut self,
+        attribute_types: &BTreeMap<String, String>,
+    ) -> Option<Result<Value, Vec<GenericError>>>;
+
+    /// Returns headers of columns of tabular streams, `None` when keys of
+    /// records are not declared up front. Called before reading records.
+    fn headers(&mut self) -> Option<Vec<String>> {
+        None
+    }
+}
@@ -0,0 +1,191 @@
+use crate::data_set::DataSet;
+use crate::errors::GenericError;
+use serde::{Serialize, Serializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[cfg(feature = "transformer")]
+use crate::data_set::JSONDataSet;
+#[cfg(feature = "transformer")]
+use crate::transformer::data_set_transformer::Operation;
+#[cfg(feature = "transformer")]
+use oca_rs::state::oca::OCA;
+
+/// Newline-delimited JSON (JSON Lines) data set holding one record per line.
+/// Blank lines are skipped.
+#[derive(Clone)]
+pub struct NDJSONDataSet {
+    pub raw: String,
+}
+
+impl Serialize for NDJSONDataSet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.raw)
+    }
+}
+
+impl DataSet for NDJSONDataSet {
+    fn new(raw: String) -> Box<Self> {
+        Box::new(Self { raw })
+    }
+
+    fn get_raw(&self) -> String {
+        self.raw.clone()
+    }
+
+    fn load(
+        &self,
+        attribute_types: BTreeMap<String, String>,
+    ) -> Result<Vec<Value>, Vec<GenericError>> {
+        let mut errors = vec![];
+        let mut result = vec![];
+        for record in self.load_records(attribute_types)? {
+            match record {
+                Ok(record) => result.push(record),
+                Err(e) => errors.extend(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(result)
+    }
+
+    /// Parses lines one by one, so invalid lines do not prevent loading
+    /// the others.
+    fn load_records(
+        &self,
+        _attribute_types: BTreeMap<String, String>,
+    ) -> Result<Vec<Result<Value, Vec<GenericError>>>, Vec<GenericError>> {
+        Ok(self
+            .raw
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| Self::parse_line(i + 1, line).map_err(|e| vec![e]))
+            .collect())
+    }
+
+    #[cfg(feature = "transformer")]
+    fn transform_schema(
+        &self,
+        mappings: BTreeMap<String, String>,
+        subset_attributes_op: Option<Vec<String>>,
+    ) -> Result<Box<dyn DataSet + Sync + Send>, GenericError> {
+        let mut lines = vec![];
+        for (i, line) in self.raw.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = JSONDataSet::transform_record_schema(
+                Self::parse_line(i + 1, line)?,
+                &mappings,
+                &subset_attributes_op,
+            );
+            lines.push(serde_json::to_string(&record)?);
+        }
+
+        Ok(Self::new(lines.join("\n")))
+    }
+
+    #[cfg(feature = "transformer")]
+    fn transform_data(
+        &self,
+        oca: &OCA,
+        entry_code_mappings: BTreeMap<String, BTreeMap<String, String>>,
+        unit_transformation_operations: BTreeMap<String, Vec<Operation>>,
+    ) -> Result<Box<dyn DataSet + Sync + Send>, Vec<GenericError>> {
+        let lines = self
+            .load(oca.capture_base.attributes.clone())?
+            .iter()
+            .map(|record| {
+                JSONDataSet::transform_record_data(
+                    self,
+                    record,
+                    &entry_code_mappings,
+                    &unit_transformation_operations,
+                )
+                .to_string()
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self::new(lines.join("\n")))
+    }
+}
+
+impl NDJSONDataSet {
+    pub(crate) fn parse_line(line_number: usize, line: &str) -> Result<Value, GenericError> {
+        serde_json::from_str(line)
+            .map_err(|e| GenericError::from(format!("Line {line_number} is not valid JSON ({e})")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_ndjson_data_set() {
+        let result = NDJSONDataSet::new(
+            "{\"email*\":\"a@a.com\"}\n\n{\"email*\":\"b@b.com\"}\r\n".to_string(),
+        )
+        .load(BTreeMap::new());
+
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                serde_json::json!({ "email*": "a@a.com" }),
+                serde_json::json!({ "email*": "b@b.com" })
+            ]
+        );
+    }
+
+    #[test]
+    fn load_ndjson_data_set_with_invalid_lines_is_err() {
+        let result =
+            NDJSONDataSet::new("{\"a\":1}\n{\"a\":\n[1,\n".to_string()).load(BTreeMap::new());
+
+        let errors = result
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Line 2 is not valid JSON"));
+        assert!(errors[1].starts_with("Line 3 is not valid JSON"));
+    }
+
+    #[test]
+    fn load_valid_records_of_ndjson_data_set_with_invalid_lines() {
+        let records = NDJSONDataSet::new("{\"a\":1}\n{\"a\":\n\n{\"a\":3}".to_string())
+            .load_records(BTreeMap::new())
+            .unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap(), &serde_json::json!({ "a": 1 }));
+        assert!(records[1].as_ref().unwrap_err()[0]
+            .to_string()
+            .starts_with("Line 2 is not valid JSON"));
+        assert_eq!(records[2].as_ref().unwrap(), &serde_json::json!({ "a": 3 }));
+    }
+
+    #[test]
+    #[cfg(feature = "transformer")]
+    fn transform_schema_of_ndjson_data_set() {
+        let data_set = NDJSONDataSet::new(
+            "{\"e-mail*\":\"a@a.com\",\"number\":1}\n{\"e-mail*\":\"b@b.com\"}".to_string(),
+        );
+        let result = data_set.transform_schema(
+            BTreeMap::from([("e-mail*".to_string(), "email*".to_string())]),
+            Some(vec!["email*".to_string()]),
+        );
+
+        assert_eq!(
+            result.unwrap().get_raw(),
+            "{\"email*\":\"a@a.com\"}\n{\"email*\":\"b@b.com\"}"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use crate::data_set::{DataStream, NDJSONDataSet};
+use crate::errors::GenericError;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Lines, Read};
+
+/// Newline-delimited JSON (JSON Lines) data set read line by line from
+/// `std::io::Read`. Blank lines are skipped.
+pub struct NDJSONDataStream<R: Read> {
+    /// `None` once reading failed, so the stream ends.
+    lines: Option<Lines<BufReader<R>>>,
+    line: usize,
+}
+
+impl<R: Read> NDJSONDataStream<R> {
+    pub fn new(source: R) -> Self {
+        Self {
+            lines: Some(BufReader::new(source).lines()),
+            line: 0,
+        }
+    }
+}
+
+impl<R: Read> DataStream for NDJSONDataStream<R> {
+    fn next_record(
+        &mut self,
+        _attribute_types: &BTreeMap<String, String>,
+    ) -> Option<Result<Value, Vec<GenericError>>> {
+        loop {
+            self.line += 1;
+            let line = match self.lines.as_mut()?.next()? {
+                Ok(line) => line,
+                Err(e) => {
+                    self.lines = None;
+                    return Some(Err(vec![GenericError::from(e)]));
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(NDJSONDataSet::parse_line(self.line, &line).map_err(|e| vec![e]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source failing on every read after given data.
+    struct FailingSource(&'static [u8]);
+
+    impl Read for FailingSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(std::io::Error::other("connection reset"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn end_stream_after_read_error() {
+        let mut stream = NDJSONDataStream::new(FailingSource(b"{\"a\": 1}\n"));
+
+        assert!(stream.next_record(&BTreeMap::new()).unwrap().is_ok());
+        let errors = stream.next_record(&BTreeMap::new()).unwrap().unwrap_err();
+        assert!(errors[0].to_string().contains("connection reset"));
+        assert!(stream.next_record(&BTreeMap::new()).is_none());
+    }
+
+    #[test]
+    fn read_json_lines_one_by_one() {
+        let mut stream = NDJSONDataStream::new("{\"a\": 1}\n\n{\"a\":\n{\"a\": 2}\n".as_bytes());
+
+        assert_eq!(
+            stream.next_record(&BTreeMap::new()).unwrap().unwrap(),
+            serde_json::json!({ "a": 1 })
+        );
+        let errors = stream.next_record(&BTreeMap::new()).unwrap().unwrap_err();
+        assert!(errors[0]
+            .to_string()
+            .starts_with("Line 3 is not valid JSON"));
+        assert_eq!(
+            stream.next_record(&BTreeMap::new()).unwrap().unwrap(),
+            serde_json::json!({ "a": 2 })
+        );
+        assert!(stream.next_record(&BTreeMap::new()).is_none());
+    }
+}
@@ -0,0 +1,445 @@
+#[cfg(feature = "transformer")]
+use crate::data_set::JSONDataSet;
+use crate::data_set::{CSVDataSet, DataSet};
+use crate::date_time_format::DateTimeFormat;
+use crate::errors::GenericError;
+use calamine::{open_workbook_from_rs, DataType, Range, Reader, Xlsx};
+use oca_rs::state::oca::{overlay, OCA};
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+
+#[cfg(feature = "transformer")]
+use crate::transformer::data_set_transformer::Operation;
+
+/// Data set read from a sheet of XLSX workbook.
+///
+/// Rows following the header row are loaded as records, fully empty rows are
+/// skipped. Numeric and boolean cells keep their native type, dates are
+/// loaded as ISO 8601 strings unless date format of the column's attribute is
+/// set.
+///
+/// Workbook content is binary, so the data set is created with
+/// [`XLSXDataSet::from_bytes`]. Its raw form and serialization are records of
+/// the sheet as JSON array.
+#[derive(Clone)]
+pub struct XLSXDataSet {
+    pub raw: Vec<u8>,
+    from_text: bool,
+    sheet: Option<String>,
+    header_row: usize,
+    header_mapping: BTreeMap<String, String>,
+    date_formats: BTreeMap<String, String>,
+}
+
+impl Serialize for XLSXDataSet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.get_raw())
+    }
+}
+
+impl DataSet for XLSXDataSet {
+    /// Workbook cannot be passed as text, loading data set created this way
+    /// fails. Use [`XLSXDataSet::from_bytes`] instead.
+    fn new(raw: String) -> Box<Self> {
+        Box::new(Self {
+            from_text: true,
+            ..*Self::from_bytes(raw.into_bytes())
+        })
+    }
+
+    /// Returns records of the sheet serialized to JSON, as workbook content
+    /// is binary.
+    fn get_raw(&self) -> String {
+        match self.load(BTreeMap::new()) {
+            Ok(records) => serde_json::to_string(&Value::Array(records)).unwrap_or_default(),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// Returns headers of the header row with header mapping applied.
+    fn headers(&self) -> Option<Vec<String>> {
+        let range = self.range().ok()?;
+        let (start_row, _) = range.start().unwrap_or_default();
+        range
+            .rows()
+            .enumerate()
+            .find(|(i, _)| start_row as usize + i >= self.header_row)
+            .map(|(_, header_cells)| self.headers_of(header_cells))
+    }
+
+    fn load(
+        &self,
+        attribute_types: BTreeMap<String, String>,
+    ) -> Result<Vec<Value>, Vec<GenericError>> {
+        let mut errors = vec![];
+        let mut result = vec![];
+
+        let range = self.range().map_err(|e| vec![e])?;
+        let (start_row, _) = range.start().unwrap_or_default();
+        let mut rows = range
+            .rows()
+            .enumerate()
+            .skip_while(|(i, _)| start_row as usize + i < self.header_row);
+        let date_formats = self
+            .date_formats
+            .iter()
+            .map(|(attr_name, pattern)| (attr_name.clone(), DateTimeFormat::new(pattern)))
+            .collect::<BTreeMap<String, DateTimeFormat>>();
+        let header_row = match rows.next() {
+            Some((_, header_cells)) => self.headers_of(header_cells),
+            None => return Ok(result),
+        };
+
+        for (_, row) in rows {
+            if row.iter().all(|cell| cell.is_empty()) {
+                continue;
+            }
+            let mut record = Map::new();
+            for (attribute_name, cell) in header_row.iter().zip(row.iter()) {
+                if attribute_name.is_empty() {
+                    continue;
+                }
+                let attribute_type = attribute_types.get(attribute_name).map(|t| t.as_str());
+                match Self::parse_cell(cell, attribute_type, date_formats.get(attribute_name)) {
+                    Ok(value) => {
+                        record.insert(attribute_name.clone(), value);
+                    }
+                    Err(e) => {
+                        errors.push(GenericError::from(format!("{attribute_name}: {e}")));
+                        record.insert(attribute_name.clone(), Value::Null);
+                    }
+                }
+            }
+            result.push(Value::Object(record));
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(result)
+    }
+
+    #[cfg(feature = "transformer")]
+    fn transform_schema(
+        &self,
+        mappings: BTreeMap<String, String>,
+        subset_attributes_op: Option<Vec<String>>,
+    ) -> Result<Box<dyn DataSet + Sync + Send>, GenericError> {
+        self.to_json_data_set(BTreeMap::new())
+            .map_err(|errors| {
+                GenericError::from(
+                    errors
+                        .iter()
+                        .map(|e| e.to_string())
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                )
+            })?
+            .transform_schema(mappings, subset_attributes_op)
+    }
+
+    #[cfg(feature = "transformer")]
+    fn transform_data(
+        &self,
+        oca: &OCA,
+        entry_code_mappings: BTreeMap<String, BTreeMap<String, String>>,
+        unit_transformation_operations: BTreeMap<String, Vec<Operation>>,
+    ) -> Result<Box<dyn DataSet + Sync + Send>, Vec<GenericError>> {
+        self.to_json_data_set(oca.capture_base.attributes.clone())?
+            .transform_data(oca, entry_code_mappings, unit_transformation_operations)
+    }
+}
+
+impl XLSXDataSet {
+    pub fn from_bytes(raw: Vec<u8>) -> Box<Self> {
+        Box::new(Self {
+            raw,
+            from_text: false,
+            sheet: None,
+            header_row: 0,
+            header_mapping: BTreeMap::new(),
+            date_formats: BTreeMap::new(),
+        })
+    }
+
+    /// Sets name of the sheet to be loaded. First sheet is used by default.
+    pub fn sheet(&mut self, name: &str) -> Self {
+        self.sheet = Some(name.to_string());
+        self.clone()
+    }
+
+    /// Sets zero-based index of the row holding column headers.
+    pub fn header_row(&mut self, index: usize) -> Self {
+        self.header_row = index;
+        self.clone()
+    }
+
+    /// Sets mapping of column headers to attribute names.
+    pub fn header_mapping(&mut self, mapping: BTreeMap<String, String>) -> Self {
+        self.header_mapping = mapping;
+        self.clone()
+    }
+
+    /// Sets date/time patterns (i.e. `DD.MM.YYYY`) used to write date cells
+    /// of given attributes.
+    pub fn date_formats(&mut self, formats: BTreeMap<String, String>) -> Self {
+        self.date_formats = formats;
+        self.clone()
+    }
+
+    /// Collects date/time patterns of DateTime attributes from Format overlay.
+    pub fn format_overlay_date_formats(oca: &OCA) -> BTreeMap<String, String> {
+        let mut formats = BTreeMap::new();
+        for overlay in &oca.overlays {
+            if overlay.overlay_type().contains("/format/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Format>() {
+                    for (attr_name, format) in &ov.attribute_formats {
+                        if let Some(attribute_type) = oca.capture_base.attributes.get(attr_name) {
+                            if attribute_type.contains("DateTime") {
+                                formats.insert(attr_name.clone(), format.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        formats
+    }
+
+    /// Builds header mapping from attribute labels defined in Label overlay
+    /// for given language.
+    pub fn label_header_mapping(oca: &OCA, language: &str) -> BTreeMap<String, String> {
+        let mut mapping = BTreeMap::new();
+        for overlay in &oca.overlays {
+            if overlay.overlay_type().contains("/label/")
+                && overlay.language().map(|l| l.as_str()) == Some(language)
+            {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Label>() {
+                    for (attr_name, label) in &ov.attribute_labels {
+                        mapping.insert(label.trim().to_string(), attr_name.clone());
+                    }
+                }
+            }
+        }
+        mapping
+    }
+
+    fn range(&self) -> Result<Range<DataType>, GenericError> {
+        if self.from_text {
+            return Err(GenericError::from(
+                "XLSX data set cannot be created from text, use XLSXDataSet::from_bytes",
+            ));
+        }
+        let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(self.raw.as_slice()))
+            .map_err(|e| GenericError::from(format!("Cannot open workbook. {e}")))?;
+        let range = match &self.sheet {
+            Some(name) => workbook
+                .worksheet_range(name)
+                .ok_or_else(|| GenericError::from(format!("Sheet '{name}' not found")))?,
+            None => workbook
+                .worksheet_range_at(0)
+                .ok_or_else(|| GenericError::from("Workbook has no sheets"))?,
+        };
+        range.map_err(|e| GenericError::from(e.to_string()))
+    }
+
+    fn headers_of(&self, header_cells: &[DataType]) -> Vec<String> {
+        header_cells
+            .iter()
+            .map(|cell| {
+                let header = cell.to_string().trim().to_string();
+                match self.header_mapping.get(&header) {
+                    Some(attribute_name) => attribute_name.clone(),
+                    None => header,
+                }
+            })
+            .collect()
+    }
+
+    fn parse_cell(
+        cell: &DataType,
+        attribute_type: Option<&str>,
+        date_format: Option<&DateTimeFormat>,
+    ) -> Result<Value, GenericError> {
+        let value = match cell {
+            DataType::Empty => Value::Null,
+            DataType::String(v) => {
+                if v.is_empty() {
+                    return Ok(Value::Null);
+                }
+                if v.trim().is_empty() {
+                    return Ok(Value::String(v.clone()));
+                }
+                let value = Value::String(v.clone());
+                return match attribute_type {
+                    Some(attribute_type) => CSVDataSet::parse_value(&value, attribute_type),
+                    None => Ok(value),
+                };
+            }
+            DataType::Int(v) => Value::from(*v),
+            DataType::Float(v) => {
+                if v.fract() == 0.0 && v.abs() < i64::MAX as f64 {
+                    Value::from(*v as i64)
+                } else {
+                    serde_json::Number::from_f64(*v)
+                        .map(Value::Number)
+                        .unwrap_or(Value::Null)
+                }
+            }
+            DataType::Bool(v) => Value::Bool(*v),
+            DataType::DateTime(_) => {
+                let datetime = cell
+                    .as_datetime()
+                    .ok_or_else(|| GenericError::from(format!("\"{cell}\" is not a valid date")))?;
+                if let Some(date_format) = date_format {
+                    Value::String(date_format.format(&datetime))
+                } else if datetime.time() == chrono::NaiveTime::MIN {
+                    Value::String(datetime.date().format("%Y-%m-%d").to_string())
+                } else {
+                    Value::String(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+                }
+            }
+            DataType::Error(e) => return Err(GenericError::from(format!("cell error {e:?}"))),
+        };
+
+        match (attribute_type, &value) {
+            (Some("Text"), Value::Number(_) | Value::Bool(_)) => {
+                Ok(Value::String(value.to_string()))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    #[cfg(feature = "transformer")]
+    fn to_json_data_set(
+        &self,
+        attribute_types: BTreeMap<String, String>,
+    ) -> Result<Box<JSONDataSet>, Vec<GenericError>> {
+        let records = self.load(attribute_types)?;
+        Ok(JSONDataSet::new(
+            serde_json::to_string(&Value::Array(records)).map_err(|e| vec![e.into()])?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_oca() -> OCA {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let oca_result = oca_zip_resolver::resolve_from_zip(
+            format!("{common_assets_dir_path}/oca_bundle.zip").as_str(),
+        );
+        assert!(oca_result.is_ok());
+        oca_result.unwrap()
+    }
+
+    fn workbook() -> Vec<u8> {
+        std::fs::read(format!(
+            "{}/assets/data_set.xlsx",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn load_xlsx_data_set() {
+        let oca = setup_oca();
+        let result = XLSXDataSet::from_bytes(workbook()).load(oca.capture_base.attributes);
+
+        assert!(result.is_ok());
+        let records = result.unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0],
+            serde_json::json!({
+                "email*": "a@example.com",
+                "licenses*": ["A"],
+                "number": 24,
+                "date": "1999-01-01",
+                "bool": true
+            })
+        );
+        assert_eq!(records[1]["number"], serde_json::json!(2.5));
+        assert_eq!(records[1]["date"], serde_json::json!("2000-01-01"));
+        assert_eq!(
+            XLSXDataSet::from_bytes(workbook()).headers().unwrap(),
+            vec!["email*", "licenses*", "number", "date", "bool"]
+        );
+    }
+
+    #[test]
+    fn load_xlsx_data_set_with_header_mapping() {
+        let oca = setup_oca();
+        let result = XLSXDataSet::from_bytes(workbook())
+            .sheet("Labels")
+            .header_row(2)
+            .header_mapping(BTreeMap::from([
+                ("E-mail".to_string(), "email*".to_string()),
+                ("Licenses".to_string(), "licenses*".to_string()),
+                ("Number".to_string(), "number".to_string()),
+            ]))
+            .load(oca.capture_base.attributes);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap(),
+            vec![serde_json::json!({
+                "email*": "a@example.com",
+                "licenses*": ["A"],
+                "number": 1
+            })]
+        );
+    }
+
+    #[cfg(feature = "validator")]
+    #[test]
+    fn validate_xlsx_data_set() {
+        let oca = setup_oca();
+        let date_formats = XLSXDataSet::format_overlay_date_formats(&oca);
+        let mut validator = crate::Validator::new(oca);
+        validator.add_data_set(Box::new(
+            XLSXDataSet::from_bytes(workbook()).date_formats(date_formats),
+        ));
+
+        assert!(validator.validate().success);
+    }
+
+    #[test]
+    fn load_xlsx_data_set_from_missing_sheet_is_err() {
+        let result = XLSXDataSet::from_bytes(workbook())
+            .sheet("Missing")
+            .load(BTreeMap::new());
+
+        assert!(result.is_err());
+    }
+    #[test]
+    fn load_xlsx_data_set_created_from_text_is_err() {
+        let data_set = XLSXDataSet::new("email*,number\na@example.com,1".to_string());
+        let result = data_set.load(BTreeMap::new());
+
+        assert!(
+            result.is_err_and(|errors| errors[0].to_string().contains("XLSXDataSet::from_bytes"))
+        );
+        assert_eq!(data_set.get_raw(), "");
+    }
+
+    #[test]
+    fn xlsx_data_set_raw_form_is_json() {
+        let raw = XLSXDataSet::from_bytes(workbook())
+            .sheet("Labels")
+            .header_row(2)
+            .get_raw();
+
+        assert_eq!(
+            serde_json::from_str::<Value>(&raw).unwrap(),
+            serde_json::json!([{ "E-mail": "a@example.com", "Licenses": "[\"A\"]", "Number": 1 }])
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use chrono::format::{parse, Parsed, StrftimeItems};
+use chrono::NaiveDateTime;
+
+/// Date/time pattern from Format overlay, i.e. `DD.MM.YYYY`,
+/// `YYYY-MM-DDThh:mm:ssZ` or `ISO 8601`.
+///
+/// Supported tokens are `YYYY`, `YY`, `MM` (month), `DD`, `hh` (or `HH`),
+/// `mm` (minutes), `ss`, `sss` (milliseconds) and `Z` (or `TZD`) standing for
+/// `Z` or `±hh:mm` offset. Other characters must appear literally. Each
+/// numeric field must be written with as many digits as its token has, i.e.
+/// `1.1.99` does not match `DD.MM.YYYY`.
+#[derive(Clone, Debug)]
+pub struct DateTimeFormat {
+    pattern: String,
+    chrono_format: String,
+    /// Expected shape of values, checked before parsing as chrono does not
+    /// enforce width of fields.
+    fields: Vec<Field>,
+    has_date: bool,
+    has_time: bool,
+    has_offset: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Field {
+    Digits(usize),
+    Offset,
+    Literal(char),
+}
+
+const TOKENS: [(&str, &str, Field); 11] = [
+    ("YYYY", "%Y", Field::Digits(4)),
+    ("YY", "%y", Field::Digits(2)),
+    ("MM", "%m", Field::Digits(2)),
+    ("DD", "%d", Field::Digits(2)),
+    ("hh", "%H", Field::Digits(2)),
+    ("HH", "%H", Field::Digits(2)),
+    ("mm", "%M", Field::Digits(2)),
+    ("sss", "%3f", Field::Digits(3)),
+    ("ss", "%S", Field::Digits(2)),
+    ("TZD", "%:z", Field::Offset),
+    ("Z", "%:z", Field::Offset),
+];
+
+impl DateTimeFormat {
+    pub fn new(pattern: &str) -> Self {
+        let normalized = match pattern.trim() {
+            "ISO 8601" | "ISO8601" | "iso8601" => "YYYY-MM-DDThh:mm:ssZ",
+            "ISO 8601 date" => "YYYY-MM-DD",
+            p => p,
+        };
+
+        let mut chrono_format = String::new();
+        let mut fields = vec![];
+        let mut rest = normalized;
+        while !rest.is_empty() {
+            match TOKENS.iter().find(|(token, _, _)| rest.starts_with(token)) {
+                Some((token, specifier, field)) => {
+                    chrono_format.push_str(specifier);
+                    fields.push(field.clone());
+                    rest = &rest[token.len()..];
+                }
+                None => {
+                    let c = rest.chars().next().unwrap();
+                    fields.push(Field::Literal(c));
+                    if c == '%' {
+                        chrono_format.push_str("%%");
+                    } else {
+                        chrono_format.push(c);
+                    }
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+
+        Self {
+            pattern: pattern.to_string(),
+            has_date: chrono_format.contains("%d"),
+            has_time: chrono_format.contains("%H"),
+            has_offset: chrono_format.contains("%:z"),
+            chrono_format,
+            fields,
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Checks whether value is written in the pattern and denotes an existing
+    /// date and time (i.e. `31.02.2000` is rejected).
+    pub fn matches(&self, value: &str) -> bool {
+        let value = match value.strip_suffix('Z') {
+            Some(v) if self.has_offset => format!("{v}+00:00"),
+            _ => value.to_string(),
+        };
+        if !self.has_shape(&value) {
+            return false;
+        }
+        let mut parsed = Parsed::new();
+        if parse(&mut parsed, &value, StrftimeItems::new(&self.chrono_format)).is_err() {
+            return false;
+        }
+        (!self.has_date || parsed.to_naive_date().is_ok())
+            && (!self.has_time || parsed.to_naive_time().is_ok())
+    }
+
+    /// Checks that each field of value has the width of its token, with
+    /// offset written as `±hh:mm`.
+    fn has_shape(&self, value: &str) -> bool {
+        let mut chars = value.chars();
+        let digits = |count: usize, chars: &mut std::str::Chars| {
+            (0..count).all(|_| chars.next().is_some_and(|c| c.is_ascii_digit()))
+        };
+        self.fields.iter().all(|field| match field {
+            Field::Digits(count) => digits(*count, &mut chars),
+            Field::Offset => {
+                matches!(chars.next(), Some('+' | '-'))
+                    && digits(2, &mut chars)
+                    && chars.next() == Some(':')
+                    && digits(2, &mut chars)
+            }
+            Field::Literal(c) => chars.next() == Some(*c),
+        }) && chars.next().is_none()
+    }
+
+    /// Writes date and time in the pattern. Offset is written as UTC.
+    pub fn format(&self, date_time: &NaiveDateTime) -> String {
+        date_time
+            .and_utc()
+            .format(&self.chrono_format)
+            .to_string()
+            .replace("+00:00", "Z")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_date_in_format() {
+        let format = DateTimeFormat::new("DD.MM.YYYY");
+        assert!(format.matches("01.01.1999"));
+        assert!(format.matches("29.02.2000"));
+        assert!(!format.matches("31.02.2000"));
+        assert!(!format.matches("1999-01-01"));
+        assert!(!format.matches("yesterday"));
+    }
+
+    #[test]
+    fn reject_fields_shorter_than_their_tokens() {
+        let format = DateTimeFormat::new("DD.MM.YYYY");
+        assert!(!format.matches("01.01.99"));
+        assert!(!format.matches("1.1.1999"));
+        assert!(!format.matches("01.01.01999"));
+        let format = DateTimeFormat::new("YYYY-MM-DD");
+        assert!(format.matches("1999-01-02"));
+        assert!(!format.matches("1999-1-2"));
+        assert!(!format.matches("99-01-02"));
+        assert!(!DateTimeFormat::new("HH:mm").matches("9:05"));
+        assert!(DateTimeFormat::new("DD.MM.YY").matches("01.01.99"));
+        assert!(!DateTimeFormat::new("DD.MM.YY").matches("01.01.1999"));
+        assert!(!DateTimeFormat::new("ISO 8601").matches("2023-01-10T21:44:00+2:00"));
+    }
+
+    #[test]
+    fn match_iso_8601_date_time() {
+        let format = DateTimeFormat::new("ISO 8601");
+        assert!(format.matches("2023-01-10T21:44:00Z"));
+        assert!(format.matches("2023-01-10T21:44:00+02:00"));
+        assert!(!format.matches("2023-01-10T25:44:00Z"));
+        assert!(!format.matches("2023-01-10"));
+    }
+
+    #[test]
+    fn match_partial_date_and_time() {
+        assert!(DateTimeFormat::new("MM/YYYY").matches("12/2020"));
+        assert!(!DateTimeFormat::new("MM/YYYY").matches("13/2020"));
+        assert!(DateTimeFormat::new("hh:mm").matches("23:59"));
+        assert!(!DateTimeFormat::new("hh:mm").matches("24:00"));
+    }
+
+    #[test]
+    fn format_date_time_in_pattern() {
+        let date_time = chrono::NaiveDate::from_ymd_opt(1999, 1, 2)
+            .unwrap()
+            .and_hms_opt(3, 4, 5)
+            .unwrap();
+        assert_eq!(
+            DateTimeFormat::new("DD.MM.YYYY").format(&date_time),
+            "02.01.1999"
+        );
+        assert_eq!(
+            DateTimeFormat::new("ISO 8601").format(&date_time),
+            "1999-01-02T03:04:05Z"
+        );
+    }
+}
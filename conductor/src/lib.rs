@@ -1,4 +1,6 @@
 pub mod data_set;
+#[cfg(any(feature = "validator", feature = "xlsx"))]
+pub mod date_time_format;
 pub mod errors;
 
 #[cfg(feature = "transformer")]
@@ -6,7 +8,14 @@ pub mod transformer;
 #[cfg(feature = "transformer")]
 pub use transformer::Transformer;
 
+#[cfg(feature = "validator")]
+pub mod validation_result;
+#[cfg(feature = "validator")]
+pub use validation_result::ValidationResult;
 #[cfg(feature = "validator")]
 pub mod validator;
 #[cfg(feature = "validator")]
 pub use validator::Validator;
+
+#[cfg(feature = "xlsx")]
+pub mod xlsx_template;
@@ -1,12 +1,13 @@
+use super::units::UnitResolver;
 use crate::data_set::DataSet;
 use oca_rs::state::oca::{overlay, DynOverlay, OCA};
-use serde_json::Value;
 use std::collections::BTreeMap;
 
 pub fn transform_pre(
     oca: &OCA,
     additional_overlays: Vec<DynOverlay>,
     data_set: Box<dyn DataSet + Sync + Send>,
+    unit_resolver: &dyn UnitResolver,
 ) -> Result<Box<dyn DataSet + Sync + Send>, Vec<String>> {
     let mut attribute_mappings: BTreeMap<String, String> = BTreeMap::new();
     let mut entry_code_mappings: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
@@ -71,8 +72,8 @@ pub fn transform_pre(
 
         for (k, source_unit) in &source_units {
             if let Some(target_unit) = target_units.get(k) {
-                if unit_transformation_operations.get(k).is_none() {
-                    match get_operations(source_unit.clone(), target_unit.clone()) {
+                if !unit_transformation_operations.contains_key(k) {
+                    match unit_resolver.resolve(source_unit, target_unit) {
                         Ok(operations) => {
                             unit_transformation_operations.insert(k.clone(), operations);
                         }
@@ -109,6 +110,7 @@ pub fn transform_post(
     oca: &OCA,
     target_overlays: &Vec<DynOverlay>,
     data_set: Box<dyn DataSet>,
+    unit_resolver: &dyn UnitResolver,
 ) -> Result<Box<dyn DataSet>, Vec<String>> {
     let mut attribute_mappings: BTreeMap<String, String> = BTreeMap::new();
     let mut entry_code_mappings: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
@@ -154,8 +156,8 @@ pub fn transform_post(
 
         for (k, target_unit) in &target_units {
             if let Some(source_unit) = source_units.get(k) {
-                if unit_transformation_operations.get(k).is_none() {
-                    match get_operations(source_unit.clone(), target_unit.clone()) {
+                if !unit_transformation_operations.contains_key(k) {
+                    match unit_resolver.resolve(source_unit, target_unit) {
                         Ok(operations) => {
                             unit_transformation_operations.insert(k.clone(), operations);
                         }
@@ -188,7 +190,7 @@ pub fn transform_post(
     Ok(transformed_data_set)
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum OpType {
     Multiply,
     Divide,
@@ -196,7 +198,7 @@ pub enum OpType {
     Subtract,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Operation {
     pub op: OpType,
     pub value: f64,
@@ -208,65 +210,6 @@ impl Operation {
     }
 }
 
-fn get_operations(source_unit: String, target_unit: String) -> Result<Vec<Operation>, Vec<String>> {
-    let mut operations = vec![];
-
-    let request_url = format!("https://repository-old.oca.argo.colossi.network/api/v0.1/transformations/units?source={}&target={}",
-            source_unit, target_unit);
-    let response = reqwest::blocking::get(&request_url);
-    match response {
-        Ok(res) => {
-            let result = res.json::<Value>();
-            if let Ok(Value::Object(r)) = result {
-                if r.get("success").unwrap().as_bool().unwrap() {
-                    let ops = r
-                        .get("result")
-                        .unwrap()
-                        .get(format!("{}->{}", source_unit, target_unit))
-                        .unwrap()
-                        .as_array()
-                        .unwrap();
-                    for op in ops {
-                        let o = op.as_object().unwrap();
-                        if let Value::String(op_sign) = o.get("op").unwrap() {
-                            if op_sign.eq("*") {
-                                operations.push(Operation::new(
-                                    OpType::Multiply,
-                                    o.get("value").unwrap().as_f64().unwrap(),
-                                ));
-                            } else if op_sign.eq("/") {
-                                operations.push(Operation::new(
-                                    OpType::Divide,
-                                    o.get("value").unwrap().as_f64().unwrap(),
-                                ));
-                            } else if op_sign.eq("+") {
-                                operations.push(Operation::new(
-                                    OpType::Add,
-                                    o.get("value").unwrap().as_f64().unwrap(),
-                                ));
-                            } else if op_sign.eq("-") {
-                                operations.push(Operation::new(
-                                    OpType::Subtract,
-                                    o.get("value").unwrap().as_f64().unwrap(),
-                                ));
-                            }
-                        }
-                    }
-                } else {
-                    return Err(vec![r.get("error").unwrap().as_str().unwrap().to_string()]);
-                }
-            } else {
-                return Err(vec![
-                    "Error while transforming units. OCA Repository cannot return operations for unit transformation.".to_string()
-                ]);
-            }
-        }
-        Err(error) => return Err(vec![error.to_string()]),
-    }
-
-    Ok(operations)
-}
-
 fn get_attribute_mappings(overlay: &DynOverlay) -> Option<BTreeMap<String, String>> {
     if overlay.overlay_type().contains("/mapping/") {
         let ov = overlay
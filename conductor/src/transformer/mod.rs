@@ -1,35 +1,65 @@
 pub mod data_set_transformer;
+pub mod units;
 
 use crate::data_set::DataSet;
 use crate::errors::GenericError;
-use crate::{validator::ConstraintsConfig, Validator};
+use crate::validator::{ConstraintsConfig, Severity};
+use crate::Validator;
 use oca_rs::controller::load_oca;
 use oca_rs::state::oca::{DynOverlay, OCA};
+use units::{BuiltInUnitResolver, UnitResolver};
 
 pub struct Transformer {
     oca: OCA,
     validator: Validator,
     data_sets: Vec<Box<dyn DataSet>>,
+    unit_resolver: Box<dyn UnitResolver>,
 }
 
 impl Transformer {
     pub fn new(oca: OCA) -> Self {
-        let mut validator = Validator::new(
+        let validator = Validator::new(
             load_oca(&mut serde_json::to_string(&oca).unwrap().as_bytes())
                 .unwrap()
                 .finalize(),
         );
+        Self::with_validator(oca, validator)
+    }
+
+    /// Creates transformer validating added data sets with validator prepared
+    /// for the OCA, e.g. with its references and code lists already added.
+    pub fn with_validator(oca: OCA, mut validator: Validator) -> Self {
         validator.set_constraints(ConstraintsConfig {
             fail_on_additional_attributes: true,
+            ..Default::default()
         });
 
         Self {
             oca,
             validator,
             data_sets: vec![],
+            unit_resolver: Box::new(BuiltInUnitResolver),
         }
     }
 
+    pub fn set_unit_resolver(&mut self, unit_resolver: Box<dyn UnitResolver>) {
+        self.unit_resolver = unit_resolver;
+    }
+
+    /// Adds OCA of capture base referenced by `Reference:SAID` attributes, so
+    /// values of such attributes in added data sets are validated against it.
+    pub fn add_reference(&mut self, said: &str, oca: OCA) -> &mut Self {
+        self.validator.add_reference(said, oca);
+        self
+    }
+
+    /// Adds code list referenced by SAID from Entry Code overlay, used when
+    /// validating added data sets.
+    pub fn add_code_list(&mut self, said: &str, codes: Vec<String>) -> &mut Self {
+        self.validator.add_code_list(said, codes);
+        self
+    }
+
     pub fn add_data_set(
         &mut self,
         data_set: Box<dyn DataSet + Sync + Send>,
@@ -62,8 +92,12 @@ impl Transformer {
             }
 
             if errors.is_empty() {
-                let result =
-                    data_set_transformer::transform_pre(&self.oca, additional_overlays, data_set);
+                let result = data_set_transformer::transform_pre(
+                    &self.oca,
+                    additional_overlays,
+                    data_set,
+                    self.unit_resolver.as_ref(),
+                );
 
                 match result {
                     Ok(data_set) => transformed_data_set = data_set,
@@ -78,15 +112,15 @@ impl Transformer {
 
         self.validator.data_sets = vec![];
         self.validator.add_data_set(transformed_data_set.clone());
-        match self.validator.validate() {
-            Ok(_) => {
-                self.data_sets.push(transformed_data_set);
-                Ok(self)
-            }
-            Err(errors) => Err(errors
-                .iter()
+        let result = self.validator.validate();
+        if result.success {
+            self.data_sets.push(transformed_data_set);
+            Ok(self)
+        } else {
+            Err(result
+                .with_severity(Severity::Error)
                 .map(|e| GenericError::from(e.to_string()))
-                .collect::<Vec<GenericError>>()),
+                .collect::<Vec<GenericError>>())
         }
     }
 
@@ -124,8 +158,12 @@ impl Transformer {
 
         let mut transformed_data_sets = vec![];
         for (i, data_set) in self.data_sets.iter().enumerate() {
-            let result =
-                data_set_transformer::transform_post(&self.oca, &target_overlays, data_set.clone());
+            let result = data_set_transformer::transform_post(
+                &self.oca,
+                &target_overlays,
+                data_set.clone(),
+                self.unit_resolver.as_ref(),
+            );
 
             match result {
                 Ok(data_set) => transformed_data_sets.push(data_set),
@@ -153,7 +191,7 @@ impl Transformer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::data_set::CSVDataSet;
+    use crate::data_set::{CSVDataSet, JSONDataSet, NDJSONDataSet};
 
     fn setup_oca() -> OCA {
         let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
@@ -213,8 +251,8 @@ test2@example.com;["B"]"#
         assert_eq!(
             transformer.get_raw_datasets(),
             vec![
-                "email*;licenses*\ntest@example.com;[\"A\"]",
-                "email*;licenses*\ntest2@example.com;[\"B\"]"
+                "email*;licenses*\ntest@example.com;\"[\"\"A\"\"]\"",
+                "email*;licenses*\ntest2@example.com;\"[\"\"B\"\"]\""
             ]
         )
     }
@@ -281,8 +319,8 @@ test2@example.com;["B"]"#
         assert_eq!(
             transformer.get_raw_datasets(),
             vec![
-                "email:;licenses*\ntest@example.com;[\"A\"]",
-                "email:;licenses*\ntest2@example.com;[\"B\"]"
+                "email:;licenses*\ntest@example.com;\"[\"\"A\"\"]\"",
+                "email:;licenses*\ntest2@example.com;\"[\"\"B\"\"]\""
             ]
         )
     }
@@ -314,7 +352,7 @@ a@a.com;["a"]"#
         assert!(result.is_ok());
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*\na@a.com;[\"A\"]",]
+            vec!["email*;licenses*\na@a.com;\"[\"\"A\"\"]\"",]
         )
     }
 
@@ -348,7 +386,40 @@ a@a.com;["A"]"#
 
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*\na@a.com;[\"1\"]",]
+            vec!["email*;licenses*\na@a.com;\"[\"\"1\"\"]\"",]
+        )
+    }
+
+    #[test]
+    fn transform_ndjson_data_set_with_entry_code_mapping_overlay() {
+        let oca = setup_oca();
+        let mut transformer = Transformer::new(oca);
+        transformer
+            .add_data_set(
+                NDJSONDataSet::new(
+                    "{\"email*\":\"a@a.com\",\"licenses*\":[\"A\"]}\n{\"email*\":\"b@b.com\",\"licenses*\":[\"B\"]}"
+                        .to_string(),
+                ),
+                None,
+            )
+            .unwrap()
+            .transform(vec![
+                r#"
+{
+  "attribute_entry_codes_mapping":{
+    "licenses*":["A:1", "B:2", "C:3", "D:4", "E:5"]
+  },
+  "capture_base":"Et7SxuRi_lK6blZmUO3X80Ji5lqMJe7DucrbUmhyzUzk",
+  "digest":"ECSC1gNDlNjhrTgAVEdB2rZ3puJO-zAX5rv0w3wFOSX4",
+  "type":"spec/overlays/entry_code_mapping/1.0"
+}
+              "#,
+            ])
+            .unwrap();
+
+        assert_eq!(
+            transformer.get_raw_datasets(),
+            vec!["{\"email*\":\"a@a.com\",\"licenses*\":[\"1\"]}\n{\"email*\":\"b@b.com\",\"licenses*\":[\"2\"]}"]
         )
     }
 
@@ -380,7 +451,7 @@ a@a.com;["A"];3.2808"#
         assert!(result.is_ok());
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*;number\na@a.com;[\"A\"];100.0",]
+            vec!["email*;licenses*;number\na@a.com;\"[\"\"A\"\"]\";100.0",]
         )
     }
 
@@ -415,7 +486,59 @@ a@a.com;["A"];100"#
 
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*;number\na@a.com;[\"A\"];1.0",]
+            vec!["email*;licenses*;number\na@a.com;\"[\"\"A\"\"]\";1.0",]
+        )
+    }
+
+    #[test]
+    fn transform_with_custom_unit_resolver() {
+        struct DoublingUnitResolver;
+        impl UnitResolver for DoublingUnitResolver {
+            fn resolve(
+                &self,
+                source_unit: &str,
+                target_unit: &str,
+            ) -> Result<Vec<data_set_transformer::Operation>, Vec<String>> {
+                assert_eq!(source_unit, "SI:cm");
+                assert_eq!(target_unit, "SI:m");
+                Ok(vec![data_set_transformer::Operation::new(
+                    data_set_transformer::OpType::Multiply,
+                    2.0,
+                )])
+            }
+        }
+
+        let oca = setup_oca();
+        let mut transformer = Transformer::new(oca);
+        transformer.set_unit_resolver(Box::new(DoublingUnitResolver));
+        transformer
+            .add_data_set(
+                CSVDataSet::new(
+                    r#"email*;licenses*;number
+a@a.com;["A"];100"#
+                        .to_string(),
+                ),
+                None,
+            )
+            .unwrap()
+            .transform(vec![
+                r#"
+{
+  "attribute_units": {
+    "number": "m"
+  },
+  "capture_base": "Et7SxuRi_lK6blZmUO3X80Ji5lqMJe7DucrbUmhyzUzk",
+  "digest": "EUMLN2GgAJopClsmoAMnvKTif8xJt-exfrJkOcdOYV_0",
+  "metric_system": "SI",
+  "type": "spec/overlays/unit/1.0"
+}
+              "#,
+            ])
+            .unwrap();
+
+        assert_eq!(
+            transformer.get_raw_datasets(),
+            vec!["email*;licenses*;number\na@a.com;\"[\"\"A\"\"]\";200.0",]
         )
     }
 
@@ -444,7 +567,7 @@ a@a.com;["A"];100"#
         assert!(result.is_ok());
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*\na@a.com;[\"A\"]",]
+            vec!["email*;licenses*\na@a.com;\"[\"\"A\"\"]\"",]
         )
     }
 
@@ -476,7 +599,7 @@ a@a.com;["A"];100"#
         assert!(result.is_ok());
         assert_eq!(
             transformer.get_raw_datasets(),
-            vec!["email*;licenses*\na@a.com;[\"A\"]",]
+            vec!["email*;licenses*\na@a.com;\"[\"\"A\"\"]\"",]
         )
     }
 
@@ -495,4 +618,40 @@ test@example.com;["A"]"#
 
         assert!(result.is_err())
     }
+
+    #[test]
+    fn validate_data_set_against_references_and_code_lists() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut transformer = Transformer::new(bundle.oca);
+        for (said, reference) in bundle.references {
+            transformer.add_reference(&said, reference);
+        }
+        for (said, codes) in bundle.code_lists {
+            transformer.add_code_list(&said, codes);
+        }
+
+        let result = transformer.add_data_set(
+            JSONDataSet::new(
+                r#"[{ "code": "glucose", "subject": { "name": "John", "birth_date": "31.02.1990" } }]"#
+                    .to_string(),
+            ),
+            None,
+        );
+        let errors = result.err().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("subject.birth_date"));
+
+        let result = transformer.add_data_set(
+            JSONDataSet::new(
+                r#"[{ "code": "glucose", "subject": { "name": "John", "birth_date": "28.02.1990" } }]"#
+                    .to_string(),
+            ),
+            None,
+        );
+        assert!(result.is_ok(), "{:?}", result.err());
+    }
 }
@@ -0,0 +1,650 @@
+use super::data_set_transformer::{OpType, Operation};
+#[cfg(feature = "remote_units")]
+use serde_json::Value;
+
+/// Resolves operations transforming value expressed in source unit into
+/// target unit.
+///
+/// Units are passed in `<metric_system>:<unit>` form (e.g. `SI:cm`), as
+/// collected from Unit overlays.
+pub trait UnitResolver {
+    fn resolve(&self, source_unit: &str, target_unit: &str) -> Result<Vec<Operation>, Vec<String>>;
+}
+
+/// Resolver backed by the built-in conversion table. With `remote_units`
+/// feature enabled, unit pairs missing from the table are resolved by OCA
+/// Repository.
+#[derive(Clone, Default)]
+pub struct BuiltInUnitResolver;
+
+impl UnitResolver for BuiltInUnitResolver {
+    fn resolve(&self, source_unit: &str, target_unit: &str) -> Result<Vec<Operation>, Vec<String>> {
+        match get_operations(source_unit, target_unit) {
+            Ok(operations) => Ok(operations),
+            #[cfg(feature = "remote_units")]
+            Err(_) => RemoteUnitResolver.resolve(source_unit, target_unit),
+            #[cfg(not(feature = "remote_units"))]
+            Err(errors) => Err(errors),
+        }
+    }
+}
+
+/// Resolver querying OCA Repository for unit transformations.
+#[cfg(feature = "remote_units")]
+#[derive(Clone, Default)]
+pub struct RemoteUnitResolver;
+
+#[cfg(feature = "remote_units")]
+impl UnitResolver for RemoteUnitResolver {
+    fn resolve(&self, source_unit: &str, target_unit: &str) -> Result<Vec<Operation>, Vec<String>> {
+        let mut operations = vec![];
+
+        let request_url = format!("https://repository-old.oca.argo.colossi.network/api/v0.1/transformations/units?source={}&target={}",
+                source_unit, target_unit);
+        let response = reqwest::blocking::get(&request_url);
+        match response {
+            Ok(res) => {
+                let result = res.json::<Value>();
+                if let Ok(Value::Object(r)) = result {
+                    if r.get("success").unwrap().as_bool().unwrap() {
+                        let ops = r
+                            .get("result")
+                            .unwrap()
+                            .get(format!("{}->{}", source_unit, target_unit))
+                            .unwrap()
+                            .as_array()
+                            .unwrap();
+                        for op in ops {
+                            let o = op.as_object().unwrap();
+                            if let Value::String(op_sign) = o.get("op").unwrap() {
+                                if op_sign.eq("*") {
+                                    operations.push(Operation::new(
+                                        OpType::Multiply,
+                                        o.get("value").unwrap().as_f64().unwrap(),
+                                    ));
+                                } else if op_sign.eq("/") {
+                                    operations.push(Operation::new(
+                                        OpType::Divide,
+                                        o.get("value").unwrap().as_f64().unwrap(),
+                                    ));
+                                } else if op_sign.eq("+") {
+                                    operations.push(Operation::new(
+                                        OpType::Add,
+                                        o.get("value").unwrap().as_f64().unwrap(),
+                                    ));
+                                } else if op_sign.eq("-") {
+                                    operations.push(Operation::new(
+                                        OpType::Subtract,
+                                        o.get("value").unwrap().as_f64().unwrap(),
+                                    ));
+                                }
+                            }
+                        }
+                    } else {
+                        return Err(vec![r.get("error").unwrap().as_str().unwrap().to_string()]);
+                    }
+                } else {
+                    return Err(vec![
+                        "Error while transforming units. OCA Repository cannot return operations for unit transformation.".to_string()
+                    ]);
+                }
+            }
+            Err(error) => return Err(vec![error.to_string()]),
+        }
+
+        Ok(operations)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Dimension {
+    Length,
+    Mass,
+    Volume,
+    Temperature,
+    Time,
+    Pressure,
+    Energy,
+    MassConcentration,
+    AmountConcentration,
+}
+
+struct UnitDefinition {
+    symbols: &'static [&'static str],
+    dimension: Dimension,
+    /// Operations converting a value in this unit to the base unit of its dimension.
+    to_base: &'static [Operation],
+}
+
+/// Analyte whose molar mass allows conversion between molar and mass
+/// concentration, e.g. `mmol/L:glucose` into `mg/dL:glucose`.
+struct Analyte {
+    name: &'static str,
+    /// Molar mass in g/mol.
+    molar_mass: f64,
+}
+
+const fn mul(value: f64) -> Operation {
+    Operation {
+        op: OpType::Multiply,
+        value,
+    }
+}
+
+const fn div(value: f64) -> Operation {
+    Operation {
+        op: OpType::Divide,
+        value,
+    }
+}
+
+const fn sub(value: f64) -> Operation {
+    Operation {
+        op: OpType::Subtract,
+        value,
+    }
+}
+
+// Factors follow the ones served by the OCA Repository so transformed values
+// stay the same as before, e.g. `ft` is defined as `m / 3.2808`.
+static UNITS: &[UnitDefinition] = &[
+    // Length, base: m
+    UnitDefinition {
+        symbols: &["m"],
+        dimension: Dimension::Length,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["km"],
+        dimension: Dimension::Length,
+        to_base: &[mul(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["dm"],
+        dimension: Dimension::Length,
+        to_base: &[div(10.0)],
+    },
+    UnitDefinition {
+        symbols: &["cm"],
+        dimension: Dimension::Length,
+        to_base: &[div(100.0)],
+    },
+    UnitDefinition {
+        symbols: &["mm"],
+        dimension: Dimension::Length,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µm", "um"],
+        dimension: Dimension::Length,
+        to_base: &[div(1_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["in", "[in_i]"],
+        dimension: Dimension::Length,
+        to_base: &[mul(0.0254)],
+    },
+    UnitDefinition {
+        symbols: &["ft", "[ft_i]"],
+        dimension: Dimension::Length,
+        to_base: &[div(3.2808)],
+    },
+    UnitDefinition {
+        symbols: &["yd", "[yd_i]"],
+        dimension: Dimension::Length,
+        to_base: &[mul(0.9144)],
+    },
+    UnitDefinition {
+        symbols: &["mi", "[mi_i]"],
+        dimension: Dimension::Length,
+        to_base: &[mul(1609.344)],
+    },
+    // Mass, base: g
+    UnitDefinition {
+        symbols: &["g"],
+        dimension: Dimension::Mass,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["kg"],
+        dimension: Dimension::Mass,
+        to_base: &[mul(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["mg"],
+        dimension: Dimension::Mass,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µg", "ug", "mcg"],
+        dimension: Dimension::Mass,
+        to_base: &[div(1_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["ng"],
+        dimension: Dimension::Mass,
+        to_base: &[div(1_000_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["t"],
+        dimension: Dimension::Mass,
+        to_base: &[mul(1_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["lb", "lbs", "[lb_av]"],
+        dimension: Dimension::Mass,
+        to_base: &[mul(453.59237)],
+    },
+    UnitDefinition {
+        symbols: &["oz", "[oz_av]"],
+        dimension: Dimension::Mass,
+        to_base: &[mul(28.349523125)],
+    },
+    UnitDefinition {
+        symbols: &["st", "[stone_av]"],
+        dimension: Dimension::Mass,
+        to_base: &[mul(6350.29318)],
+    },
+    // Volume, base: L
+    UnitDefinition {
+        symbols: &["L", "l"],
+        dimension: Dimension::Volume,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["dL", "dl"],
+        dimension: Dimension::Volume,
+        to_base: &[div(10.0)],
+    },
+    UnitDefinition {
+        symbols: &["cL", "cl"],
+        dimension: Dimension::Volume,
+        to_base: &[div(100.0)],
+    },
+    UnitDefinition {
+        symbols: &["mL", "ml", "cm3"],
+        dimension: Dimension::Volume,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µL", "uL", "ul"],
+        dimension: Dimension::Volume,
+        to_base: &[div(1_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["m3"],
+        dimension: Dimension::Volume,
+        to_base: &[mul(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["gal", "[gal_us]"],
+        dimension: Dimension::Volume,
+        to_base: &[mul(3.785411784)],
+    },
+    UnitDefinition {
+        symbols: &["qt", "[qt_us]"],
+        dimension: Dimension::Volume,
+        to_base: &[mul(0.946352946)],
+    },
+    UnitDefinition {
+        symbols: &["pt", "[pt_us]"],
+        dimension: Dimension::Volume,
+        to_base: &[mul(0.473176473)],
+    },
+    UnitDefinition {
+        symbols: &["fl oz", "[foz_us]"],
+        dimension: Dimension::Volume,
+        to_base: &[mul(0.0295735295625)],
+    },
+    // Temperature, base: °C
+    UnitDefinition {
+        symbols: &["°C", "degC", "Cel"],
+        dimension: Dimension::Temperature,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["K"],
+        dimension: Dimension::Temperature,
+        to_base: &[sub(273.15)],
+    },
+    UnitDefinition {
+        symbols: &["°F", "degF", "[degF]"],
+        dimension: Dimension::Temperature,
+        to_base: &[sub(32.0), div(1.8)],
+    },
+    // Time, base: s
+    UnitDefinition {
+        symbols: &["s"],
+        dimension: Dimension::Time,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["ms"],
+        dimension: Dimension::Time,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["min"],
+        dimension: Dimension::Time,
+        to_base: &[mul(60.0)],
+    },
+    UnitDefinition {
+        symbols: &["h"],
+        dimension: Dimension::Time,
+        to_base: &[mul(3600.0)],
+    },
+    UnitDefinition {
+        symbols: &["d"],
+        dimension: Dimension::Time,
+        to_base: &[mul(86400.0)],
+    },
+    UnitDefinition {
+        symbols: &["wk"],
+        dimension: Dimension::Time,
+        to_base: &[mul(604_800.0)],
+    },
+    // Pressure, base: Pa
+    UnitDefinition {
+        symbols: &["Pa"],
+        dimension: Dimension::Pressure,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["hPa"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(100.0)],
+    },
+    UnitDefinition {
+        symbols: &["kPa"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["bar"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(100_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["mbar"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(100.0)],
+    },
+    UnitDefinition {
+        symbols: &["mmHg", "mm[Hg]"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(133.322387415)],
+    },
+    UnitDefinition {
+        symbols: &["cmH2O", "cm[H2O]"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(98.0665)],
+    },
+    UnitDefinition {
+        symbols: &["atm"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(101_325.0)],
+    },
+    UnitDefinition {
+        symbols: &["psi", "[psi]"],
+        dimension: Dimension::Pressure,
+        to_base: &[mul(6894.757293168)],
+    },
+    // Energy, base: J
+    UnitDefinition {
+        symbols: &["J"],
+        dimension: Dimension::Energy,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["kJ"],
+        dimension: Dimension::Energy,
+        to_base: &[mul(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["cal"],
+        dimension: Dimension::Energy,
+        to_base: &[mul(4.184)],
+    },
+    UnitDefinition {
+        symbols: &["kcal", "Cal"],
+        dimension: Dimension::Energy,
+        to_base: &[mul(4184.0)],
+    },
+    UnitDefinition {
+        symbols: &["Wh"],
+        dimension: Dimension::Energy,
+        to_base: &[mul(3600.0)],
+    },
+    UnitDefinition {
+        symbols: &["kWh"],
+        dimension: Dimension::Energy,
+        to_base: &[mul(3_600_000.0)],
+    },
+    // Mass concentration, base: g/L
+    UnitDefinition {
+        symbols: &["g/L", "g/l"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["g/dL", "g/dl"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[mul(10.0)],
+    },
+    UnitDefinition {
+        symbols: &["mg/dL", "mg/dl"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[div(100.0)],
+    },
+    UnitDefinition {
+        symbols: &["mg/L", "mg/l", "µg/mL", "ug/mL"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µg/dL", "ug/dL"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[div(100_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µg/L", "ug/L", "ng/mL"],
+        dimension: Dimension::MassConcentration,
+        to_base: &[div(1_000_000.0)],
+    },
+    // Amount of substance concentration, base: mol/L
+    UnitDefinition {
+        symbols: &["mol/L", "mol/l"],
+        dimension: Dimension::AmountConcentration,
+        to_base: &[],
+    },
+    UnitDefinition {
+        symbols: &["mmol/L", "mmol/l"],
+        dimension: Dimension::AmountConcentration,
+        to_base: &[div(1000.0)],
+    },
+    UnitDefinition {
+        symbols: &["µmol/L", "umol/L", "umol/l"],
+        dimension: Dimension::AmountConcentration,
+        to_base: &[div(1_000_000.0)],
+    },
+    UnitDefinition {
+        symbols: &["nmol/L", "nmol/l"],
+        dimension: Dimension::AmountConcentration,
+        to_base: &[div(1_000_000_000.0)],
+    },
+];
+
+// Molar and mass concentrations are convertible only for units qualified with
+// one of these analytes, as the factor depends on its molar mass.
+static ANALYTES: &[Analyte] = &[
+    Analyte {
+        name: "glucose",
+        molar_mass: 180.156,
+    },
+    Analyte {
+        name: "cholesterol",
+        molar_mass: 386.65,
+    },
+    Analyte {
+        name: "creatinine",
+        molar_mass: 113.12,
+    },
+    Analyte {
+        name: "urea",
+        molar_mass: 60.06,
+    },
+];
+
+// Metric system prefix is not taken into account when looking up the unit.
+// Unit may be qualified with measured analyte, e.g. `nonSI:mmol/L:glucose`.
+fn get_operations(source_unit: &str, target_unit: &str) -> Result<Vec<Operation>, Vec<String>> {
+    let (source, source_analyte) = parse_unit(source_unit)
+        .ok_or_else(|| vec![format!("Unsupported unit: {}", source_unit)])?;
+    let (target, target_analyte) = parse_unit(target_unit)
+        .ok_or_else(|| vec![format!("Unsupported unit: {}", target_unit)])?;
+    let not_convertible = || {
+        vec![format!(
+            "Cannot transform {} into {}. Units are not convertible.",
+            source_unit, target_unit
+        )]
+    };
+
+    let analyte = match (source_analyte, target_analyte) {
+        (Some(source_analyte), Some(target_analyte)) if source_analyte != target_analyte => {
+            return Err(not_convertible())
+        }
+        (source_analyte, target_analyte) => source_analyte.or(target_analyte),
+    };
+
+    if source.dimension == target.dimension {
+        return Ok(convert(source, target));
+    }
+
+    let molar_mass = analyte
+        .and_then(|analyte| ANALYTES.iter().find(|a| a.name == analyte))
+        .map(|analyte| analyte.molar_mass)
+        .ok_or_else(not_convertible)?;
+    let bridge = match (source.dimension, target.dimension) {
+        (Dimension::AmountConcentration, Dimension::MassConcentration) => mul(molar_mass),
+        (Dimension::MassConcentration, Dimension::AmountConcentration) => div(molar_mass),
+        _ => return Err(not_convertible()),
+    };
+    let mut operations = source.to_base.to_vec();
+    operations.push(bridge);
+    operations.extend(target.to_base.iter().rev().map(inverse));
+    Ok(operations)
+}
+
+/// Splits unit into its definition and optional analyte, skipping the metric
+/// system prefix.
+fn parse_unit(unit: &str) -> Option<(&'static UnitDefinition, Option<String>)> {
+    let mut segments = unit.split(':');
+    let first = segments.next()?;
+    let (definition, analyte) = match find_unit(first) {
+        Some(definition) => (definition, segments.next()),
+        None => (find_unit(segments.next()?)?, segments.next()),
+    };
+    if segments.next().is_some() {
+        return None;
+    }
+    Some((definition, analyte.map(|a| a.trim().to_lowercase())))
+}
+
+fn find_unit(symbol: &str) -> Option<&'static UnitDefinition> {
+    let symbol = symbol.trim().replace('μ', "µ");
+    UNITS
+        .iter()
+        .find(|definition| definition.symbols.contains(&symbol.as_str()))
+}
+
+fn convert(source: &UnitDefinition, target: &UnitDefinition) -> Vec<Operation> {
+    if std::ptr::eq(source, target) {
+        return vec![];
+    }
+    let mut operations = source.to_base.to_vec();
+    operations.extend(target.to_base.iter().rev().map(inverse));
+    operations
+}
+
+fn inverse(operation: &Operation) -> Operation {
+    let op = match operation.op {
+        OpType::Multiply => OpType::Divide,
+        OpType::Divide => OpType::Multiply,
+        OpType::Add => OpType::Subtract,
+        OpType::Subtract => OpType::Add,
+    };
+    Operation::new(op, operation.value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(value: f64, operations: &[Operation]) -> f64 {
+        operations
+            .iter()
+            .fold(value, |result, operation| match operation.op {
+                OpType::Multiply => result * operation.value,
+                OpType::Divide => result / operation.value,
+                OpType::Add => result + operation.value,
+                OpType::Subtract => result - operation.value,
+            })
+    }
+
+    #[test]
+    fn same_unit_requires_no_operations() {
+        let operations = get_operations("SI:cm", "IU:cm").unwrap();
+        assert!(operations.is_empty());
+    }
+
+    #[test]
+    fn convert_length_units() {
+        let operations = get_operations("IU:ft", "SI:cm").unwrap();
+        assert_eq!(
+            operations,
+            vec![
+                Operation::new(OpType::Divide, 3.2808),
+                Operation::new(OpType::Multiply, 100.0)
+            ]
+        );
+        assert_eq!(apply(3.2808, &operations), 100.0);
+        assert_eq!(apply(250.0, &get_operations("SI:cm", "SI:m").unwrap()), 2.5);
+    }
+
+    #[test]
+    fn convert_temperature_units() {
+        let operations = get_operations("IU:°F", "SI:°C").unwrap();
+        assert_eq!(apply(212.0, &operations), 100.0);
+        let operations = get_operations("SI:°C", "IU:°F").unwrap();
+        assert_eq!(apply(100.0, &operations), 212.0);
+        let operations = get_operations("SI:K", "SI:°C").unwrap();
+        assert_eq!(apply(273.15, &operations), 0.0);
+    }
+
+    #[test]
+    fn convert_clinical_units() {
+        let operations = get_operations("nonSI:mg/dL:glucose", "nonSI:mmol/L:glucose").unwrap();
+        assert!((apply(90.078, &operations) - 5.0).abs() < 1e-9);
+        let operations = get_operations("nonSI:mmol/L:glucose", "nonSI:mg/dL").unwrap();
+        assert!((apply(5.0, &operations) - 90.078).abs() < 1e-9);
+        let operations = get_operations("SI:µmol/L:creatinine", "nonSI:mg/dL:creatinine").unwrap();
+        assert!((apply(88.4, &operations) - 1.0).abs() < 1e-3);
+        let operations = get_operations("mg/dL:glucose", "g/L:glucose").unwrap();
+        assert_eq!(apply(100.0, &operations), 1.0);
+        let operations = get_operations("SI:mmHg", "SI:kPa").unwrap();
+        assert!((apply(120.0, &operations) - 15.998686).abs() < 1e-6);
+    }
+
+    #[test]
+    fn convert_between_incompatible_units_is_err() {
+        assert!(get_operations("SI:kg", "SI:m").is_err());
+        assert!(get_operations("SI:unknown", "SI:m").is_err());
+        assert!(get_operations("SI:nmol/L", "SI:ng/mL").is_err());
+        assert!(get_operations("SI:µmol/L", "nonSI:mg/dL").is_err());
+        assert!(get_operations("nonSI:mmol/L", "SI:g/L").is_err());
+        assert!(get_operations("nonSI:mmol/L", "nonSI:mg/dL").is_err());
+        assert!(get_operations("nonSI:mg/dL", "nonSI:mmol/L").is_err());
+        assert!(get_operations("nonSI:mmol/L:albumin", "nonSI:mg/dL").is_err());
+        assert!(get_operations("nonSI:mmol/L:glucose", "nonSI:mg/dL:urea").is_err());
+    }
+}
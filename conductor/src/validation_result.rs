@@ -1,9 +1,16 @@
+use crate::validator::{Severity, ValidationError};
 use serde::Serialize;
 
-#[derive(Serialize)]
+/// Findings of validation with their numbers per severity. Validation is
+/// successful when none of the findings is an error.
+#[derive(Debug, Serialize)]
 pub struct ValidationResult {
     pub success: bool,
-    pub errors: Vec<String>,
+    /// Findings of all severities in order they were found.
+    pub errors: Vec<ValidationError>,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub info_count: usize,
 }
 
 impl Default for ValidationResult {
@@ -12,21 +19,69 @@ impl Default for ValidationResult {
     }
 }
 
+impl From<Vec<ValidationError>> for ValidationResult {
+    fn from(errors: Vec<ValidationError>) -> Self {
+        let mut result = Self::new();
+        result.add_errors(errors);
+        result
+    }
+}
+
 impl ValidationResult {
     pub fn new() -> ValidationResult {
         ValidationResult {
             success: true,
             errors: vec![],
+            error_count: 0,
+            warning_count: 0,
+            info_count: 0,
         }
     }
 
-    pub fn add_error(&mut self, error: String) {
-        self.success = false;
+    pub fn add_error(&mut self, error: ValidationError) {
+        match error.severity {
+            Severity::Error => {
+                self.success = false;
+                self.error_count += 1;
+            }
+            Severity::Warning => self.warning_count += 1,
+            Severity::Info => self.info_count += 1,
+        }
         self.errors.push(error);
     }
 
-    pub fn add_errors(&mut self, errors: Vec<String>) {
-        self.success = false;
-        self.errors.extend(errors);
+    pub fn add_errors(&mut self, errors: impl IntoIterator<Item = ValidationError>) {
+        for error in errors {
+            self.add_error(error);
+        }
+    }
+
+    /// Returns findings of given severity.
+    pub fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &ValidationError> {
+        self.errors.iter().filter(move |e| e.severity == severity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::ValidationErrorKind;
+
+    #[test]
+    fn count_findings_per_severity() {
+        let finding = |kind| ValidationError::new("0".into(), "0".into(), "a".into(), kind);
+        let result = ValidationResult::from(vec![
+            finding(ValidationErrorKind::EmptyValue),
+            finding(ValidationErrorKind::MissingAttribute),
+            finding(ValidationErrorKind::EmptyValue),
+        ]);
+
+        assert!(!result.success);
+        assert_eq!(
+            (result.error_count, result.warning_count, result.info_count),
+            (1, 2, 0)
+        );
+        assert_eq!(result.with_severity(Severity::Warning).count(), 2);
+        assert!(ValidationResult::from(vec![finding(ValidationErrorKind::EmptyValue)]).success);
     }
 }
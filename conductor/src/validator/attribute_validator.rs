@@ -1,12 +1,35 @@
+use super::condition::Condition;
+use super::format_constraints::FormatConstraints;
+use crate::date_time_format::DateTimeFormat;
 use oca_rs::state::{attribute::AttributeType, encoding::Encoding, entry_codes::EntryCodes};
+use regex::Regex;
 
+/// Entry of Format overlay compiled once per attribute, so it is not parsed
+/// again for every validated value.
+#[derive(Clone)]
+pub enum CompiledFormat {
+    Constraints(FormatConstraints),
+    Regex(Regex),
+    DateTime(DateTimeFormat),
+    /// Regex or constraints which cannot be compiled.
+    Invalid,
+}
+
+#[derive(Clone)]
 pub struct AttributeValidator {
     pub attribute_name: String,
     pub attribute_type: AttributeType,
     pub entry_codes: Option<EntryCodes>,
     pub format: Option<String>,
+    pub compiled_format: Option<CompiledFormat>,
     pub conformance: Option<String>,
     pub encoding: Option<Encoding>,
+    /// Allowed number of array elements, i.e. `1-3`, `2-` or `2`.
+    pub cardinality: Option<String>,
+    /// Condition from Conditional overlay under which attribute is relevant.
+    pub condition: Option<Condition>,
+    /// SAID of capture base referenced by Reference attribute.
+    pub reference: Option<String>,
 }
 
 impl AttributeValidator {
@@ -15,29 +38,71 @@ impl AttributeValidator {
             attribute_name,
             attribute_type,
             format: None,
+            compiled_format: None,
             entry_codes: None,
             encoding: None,
             conformance: None,
+            cardinality: None,
+            condition: None,
+            reference: None,
         }
     }
 
-    pub fn element(&self, index: usize) -> Option<AttributeValidator> {
-        let attribute_type = match self.attribute_type {
-            AttributeType::ArrayText => AttributeType::Text,
-            AttributeType::ArrayNumeric => AttributeType::Numeric,
-            AttributeType::ArrayBoolean => AttributeType::Boolean,
-            AttributeType::ArrayDateTime => AttributeType::DateTime,
-            AttributeType::ArrayBinary => AttributeType::Binary,
-            AttributeType::ArrayReference => AttributeType::Reference,
-            _ => return None,
+    /// Sets Format overlay entry and compiles it according to attribute type
+    /// (or type of array elements).
+    pub fn set_format(&mut self, format: String) {
+        self.compiled_format = match self.attribute_type {
+            AttributeType::DateTime | AttributeType::ArrayDateTime => {
+                Some(CompiledFormat::DateTime(DateTimeFormat::new(&format)))
+            }
+            AttributeType::Text
+            | AttributeType::ArrayText
+            | AttributeType::Numeric
+            | AttributeType::ArrayNumeric => match FormatConstraints::parse(
+                &format,
+                matches!(
+                    self.attribute_type,
+                    AttributeType::Numeric | AttributeType::ArrayNumeric
+                ),
+            ) {
+                Some(Ok(constraints)) => Some(CompiledFormat::Constraints(constraints)),
+                Some(Err(_)) => Some(CompiledFormat::Invalid),
+                None => match Regex::new(format!("^{}$", format).as_str()) {
+                    Ok(regex) => Some(CompiledFormat::Regex(regex)),
+                    Err(_) => Some(CompiledFormat::Invalid),
+                },
+            },
+            _ => None,
         };
+        self.format = Some(format);
+    }
+
+    pub fn element(&self, index: usize) -> Option<AttributeValidator> {
+        let attribute_type = element_type(self.attribute_type)?;
         Some(AttributeValidator {
             attribute_name: format!("{}[{}]", self.attribute_name, index),
             attribute_type,
             format: self.format.clone(),
+            compiled_format: self.compiled_format.clone(),
             entry_codes: self.entry_codes.clone(),
             conformance: self.conformance.clone(),
             encoding: self.encoding,
+            cardinality: None,
+            condition: None,
+            reference: self.reference.clone(),
         })
     }
 }
+
+/// Returns type of elements of array type.
+pub fn element_type(attribute_type: AttributeType) -> Option<AttributeType> {
+    match attribute_type {
+        AttributeType::ArrayText => Some(AttributeType::Text),
+        AttributeType::ArrayNumeric => Some(AttributeType::Numeric),
+        AttributeType::ArrayBoolean => Some(AttributeType::Boolean),
+        AttributeType::ArrayDateTime => Some(AttributeType::DateTime),
+        AttributeType::ArrayBinary => Some(AttributeType::Binary),
+        AttributeType::ArrayReference => Some(AttributeType::Reference),
+        _ => None,
+    }
+}
@@ -0,0 +1,9 @@
+/// Provides code lists referenced by SAID from Entry Code overlay
+/// (`EntryCodes::Sai`) which are kept outside of OCA Bundle.
+///
+/// `code_list` is called for every validated value of attribute using the
+/// code list, so implementations fetching code lists from remote sources
+/// should cache them.
+pub trait CodeListProvider {
+    fn code_list(&self, said: &str) -> Option<Vec<String>>;
+}
@@ -0,0 +1,289 @@
+use oca_rs::state::attribute::AttributeType;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+use super::attribute_validator::{element_type, AttributeValidator};
+use super::{Severity, ValidationError, Validator};
+use crate::data_set::DataSet;
+
+/// Record with values normalised to types of its attributes together with
+/// errors found in it.
+#[derive(Debug, Serialize)]
+pub struct CoercedRecord {
+    pub record: Value,
+    pub errors: Vec<ValidationError>,
+}
+
+impl Validator {
+    /// Normalises records of given data set and validates them, so they can
+    /// be passed on without parsing again:
+    /// - strings are trimmed and empty strings become `null`,
+    /// - numeric strings of Numeric attributes become numbers,
+    /// - `true`/`false`, `yes`/`no` and `1`/`0` of Boolean attributes become
+    ///   booleans.
+    ///
+    /// Values which cannot be coerced are kept as they are and reported as
+    /// errors of their record. Records which cannot be loaded become `null`
+    /// with errors of loading them. Keys are renamed to attribute names as
+    /// allowed by constraints, unknown attributes are kept untouched. Errors
+    /// of loading the data set and duplicate headers are returned as `Err`,
+    /// referring to data set `0`.
+    pub fn coerce_data_set(
+        &self,
+        data_set: &dyn DataSet,
+    ) -> Result<Vec<CoercedRecord>, Vec<ValidationError>> {
+        let headers = data_set.headers();
+        let header_errors = self.header_errors(0, headers.as_deref().unwrap_or_default());
+        if header_errors.iter().any(|e| e.severity == Severity::Error) {
+            return Err(header_errors);
+        }
+        let records = data_set
+            .load_records(self.header_types(headers.as_deref()))
+            .map_err(|errors| {
+                errors
+                    .iter()
+                    .map(|e| self.parse_error(0, "", e))
+                    .collect::<Vec<_>>()
+            })?;
+        Ok(records
+            .iter()
+            .enumerate()
+            .map(|(record_index, record)| match record {
+                Ok(record) => self.coerce(record_index, record),
+                Err(errors) => CoercedRecord {
+                    record: Value::Null,
+                    errors: errors
+                        .iter()
+                        .map(|e| self.parse_error(0, &record_index.to_string(), e))
+                        .collect(),
+                },
+            })
+            .collect())
+    }
+
+    /// Normalises and validates single record as `coerce_data_set` does.
+    /// Errors refer to data set `0` and record `0`.
+    pub fn coerce_record(&self, record: &Value) -> CoercedRecord {
+        self.coerce(0, record)
+    }
+
+    fn coerce(&self, record_index: usize, record: &Value) -> CoercedRecord {
+        let record = match record {
+            Value::Object(record) => Value::Object(
+                self.coerce_attributes(&self.resolve_headers(record), &self.attribute_validators),
+            ),
+            record => record.clone(),
+        };
+        CoercedRecord {
+            errors: self.record_errors(0, record_index, &record),
+            record,
+        }
+    }
+
+    fn coerce_attributes(
+        &self,
+        record: &Map<String, Value>,
+        attribute_validators: &HashMap<String, AttributeValidator>,
+    ) -> Map<String, Value> {
+        record
+            .iter()
+            .map(|(name, value)| {
+                let value = match attribute_validators.get(name) {
+                    Some(validator) => {
+                        self.coerce_value(value, validator.attribute_type, validator)
+                    }
+                    None => value.clone(),
+                };
+                (name.clone(), value)
+            })
+            .collect()
+    }
+
+    fn coerce_value(
+        &self,
+        value: &Value,
+        attribute_type: AttributeType,
+        validator: &AttributeValidator,
+    ) -> Value {
+        let value = match value {
+            Value::String(text) if text.trim().is_empty() => return Value::Null,
+            Value::String(text) => Value::String(text.trim().to_string()),
+            value => value.clone(),
+        };
+        match (attribute_type, value) {
+            (AttributeType::Numeric, Value::String(text)) => {
+                parse_number(&text).map_or(Value::String(text), Value::Number)
+            }
+            (AttributeType::Boolean, value) => parse_bool(&value).map_or(value, Value::Bool),
+            (AttributeType::Reference, Value::Object(record)) => {
+                // Unresolved reference is reported when validating the record.
+                match self.referenced_validators(validator) {
+                    Ok(Some(attribute_validators)) => {
+                        Value::Object(self.coerce_attributes(&record, attribute_validators))
+                    }
+                    _ => Value::Object(record),
+                }
+            }
+            (attribute_type, Value::Array(elements)) => match element_type(attribute_type) {
+                Some(element_type) => Value::Array(
+                    elements
+                        .iter()
+                        .map(|element| self.coerce_value(element, element_type, validator))
+                        .collect(),
+                ),
+                None => Value::Array(elements),
+            },
+            (_, value) => value,
+        }
+    }
+}
+
+/// Parses number keeping integers as integers, i.e. `23` and `2.5`.
+fn parse_number(text: &str) -> Option<Number> {
+    serde_json::from_str::<Number>(text)
+        .ok()
+        .or_else(|| text.parse::<f64>().ok().and_then(Number::from_f64))
+}
+
+fn parse_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(b) => Some(*b),
+        Value::Number(n) if n.as_f64() == Some(1.0) => Some(true),
+        Value::Number(n) if n.as_f64() == Some(0.0) => Some(false),
+        Value::String(s) => match s.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Some(true),
+            "false" | "no" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_set::{CSVDataSet, JSONDataSet};
+    use crate::validator::ValidationErrorKind;
+    use oca_rs::state::oca::OCA;
+    use serde_json::json;
+
+    fn setup_oca() -> OCA {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        oca_zip_resolver::resolve_from_zip(
+            format!("{}/oca_bundle.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn coerce_json_record_to_attribute_types() {
+        let validator = Validator::new(setup_oca());
+        let coerced = validator.coerce_record(&json!({
+            "email*": " test@example.com ",
+            "licenses*": ["A"],
+            "number": " 24 ",
+            "numbers": [22, "23", "2.5"],
+            "date": "",
+            "bool": "yes",
+            "bools": ["no", 1, "0"],
+            "additional": " x "
+        }));
+
+        assert!(
+            coerced
+                .errors
+                .iter()
+                .all(|e| e.severity == Severity::Warning),
+            "{:?}",
+            coerced.errors
+        );
+        assert_eq!(
+            coerced.record,
+            json!({
+                "email*": "test@example.com",
+                "licenses*": ["A"],
+                "number": 24,
+                "numbers": [22, 23, 2.5],
+                "date": null,
+                "bool": true,
+                "bools": [false, true, false],
+                "additional": " x "
+            })
+        );
+    }
+
+    #[test]
+    fn coerced_record_keeps_invalid_values_with_its_errors() {
+        let validator = Validator::new(setup_oca());
+        let coerced = validator
+            .coerce_data_set(
+                JSONDataSet::new(
+                    r#"[
+  { "email*": "a@example.com", "licenses*": ["A"], "bool": "maybe" },
+  { "email*": "b@example.com", "licenses*": ["B"], "number": "1" }
+]"#
+                    .to_string(),
+                )
+                .as_ref(),
+            )
+            .unwrap();
+
+        assert_eq!(coerced.len(), 2);
+        assert_eq!(coerced[0].record["bool"], json!("maybe"));
+        assert_eq!(coerced[0].errors.len(), 1);
+        assert!(matches!(
+            coerced[0].errors[0].kind,
+            ValidationErrorKind::TypeMismatch { .. }
+        ));
+        assert_eq!(coerced[1].record["number"], json!(1));
+        assert_eq!(coerced[1].errors.len(), 0);
+    }
+
+    #[test]
+    fn coerced_record_reports_unresolved_reference() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        let coerced = validator.coerce_record(&json!({
+            "code": "glucose",
+            "subject": { "name": " John " }
+        }));
+
+        assert_eq!(coerced.record["subject"], json!({ "name": " John " }));
+        assert_eq!(coerced.errors.len(), 1);
+        assert!(matches!(
+            coerced.errors[0].kind,
+            ValidationErrorKind::UnresolvedReference { .. }
+        ));
+    }
+
+    #[test]
+    fn coerce_csv_data_set() {
+        let validator = Validator::new(setup_oca());
+        let coerced = validator
+            .coerce_data_set(
+                CSVDataSet::new(
+                    r#"email*;licenses*;number
+ test@example.com ;["A"];1.50"#
+                        .to_string(),
+                )
+                .as_ref(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            coerced[0].record,
+            json!({ "email*": "test@example.com", "licenses*": ["A"], "number": 1.5 })
+        );
+        assert!(validator
+            .coerce_data_set(JSONDataSet::new("[".to_string()).as_ref())
+            .is_err());
+    }
+}
@@ -0,0 +1,368 @@
+use serde_json::{Map, Value};
+
+/// Expression from Conditional overlay, i.e. `${0} == 'F'`, where `${n}`
+/// stands for value of n-th attribute from its dependencies.
+///
+/// Supports string, number, boolean and `null` literals, comparison
+/// (`==`, `!=`, `<`, `<=`, `>`, `>=`), logical (`&&`, `||`, `!`) operators
+/// and parentheses.
+#[derive(Clone)]
+pub struct Condition {
+    expression: String,
+    dependencies: Vec<String>,
+    parsed: Option<Expr>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Dependency(usize),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Operator, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Literal(Value),
+    Dependency(usize),
+    Operator(Operator),
+    Not,
+    And,
+    Or,
+    LeftParen,
+    RightParen,
+}
+
+impl Condition {
+    pub fn new(expression: &str, dependencies: Vec<String>) -> Self {
+        let parsed = tokenize(expression).and_then(|tokens| {
+            let mut parser = Parser {
+                tokens,
+                position: 0,
+            };
+            let expr = parser.or()?;
+            match parser.tokens.len() == parser.position
+                && expr.dependencies().iter().all(|i| *i < dependencies.len())
+            {
+                true => Some(expr),
+                false => None,
+            }
+        });
+        Self {
+            expression: expression.to_string(),
+            dependencies,
+            parsed,
+        }
+    }
+
+    /// Returns whether expression could be parsed, referring only to given
+    /// dependencies.
+    pub fn is_valid(&self) -> bool {
+        self.parsed.is_some()
+    }
+
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns expression with `${n}` placeholders replaced by names of
+    /// attributes, i.e. `sex == 'F'`.
+    pub fn describe(&self) -> String {
+        self.dependencies
+            .iter()
+            .enumerate()
+            .fold(self.expression.clone(), |expression, (i, name)| {
+                expression.replace(&format!("${{{i}}}"), name)
+            })
+    }
+
+    /// Evaluates expression against record attributes. Missing attributes are
+    /// treated as `null`. Returns `None` if expression is invalid.
+    pub fn evaluate(&self, record: &Map<String, Value>) -> Option<bool> {
+        let values = self
+            .dependencies
+            .iter()
+            .map(|name| record.get(name).unwrap_or(&Value::Null))
+            .collect::<Vec<_>>();
+        self.parsed
+            .as_ref()
+            .map(|expr| is_truthy(&expr.evaluate(&values)))
+    }
+}
+
+impl Expr {
+    fn dependencies(&self) -> Vec<usize> {
+        match self {
+            Expr::Literal(_) => vec![],
+            Expr::Dependency(i) => vec![*i],
+            Expr::Not(e) => e.dependencies(),
+            Expr::And(l, r) | Expr::Or(l, r) | Expr::Compare(l, _, r) => {
+                let mut dependencies = l.dependencies();
+                dependencies.extend(r.dependencies());
+                dependencies
+            }
+        }
+    }
+
+    fn evaluate(&self, values: &[&Value]) -> Value {
+        match self {
+            Expr::Literal(v) => v.clone(),
+            Expr::Dependency(i) => values[*i].clone(),
+            Expr::Not(e) => Value::Bool(!is_truthy(&e.evaluate(values))),
+            Expr::And(l, r) => {
+                Value::Bool(is_truthy(&l.evaluate(values)) && is_truthy(&r.evaluate(values)))
+            }
+            Expr::Or(l, r) => {
+                Value::Bool(is_truthy(&l.evaluate(values)) || is_truthy(&r.evaluate(values)))
+            }
+            Expr::Compare(l, op, r) => {
+                Value::Bool(compare(&l.evaluate(values), *op, &r.evaluate(values)))
+            }
+        }
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+    }
+}
+
+/// Compares values as numbers when both are numeric (numeric strings
+/// included, as data sets may hold numbers as text), otherwise as strings.
+/// Only equality is defined for other values.
+fn compare(left: &Value, op: Operator, right: &Value) -> bool {
+    let as_number = |v: &Value| match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    let ordering = match (as_number(left), as_number(right), left, right) {
+        (Some(l), Some(r), _, _) => l.partial_cmp(&r),
+        (_, _, Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => match op {
+            Operator::Eq => return left == right,
+            Operator::Ne => return left != right,
+            _ => return false,
+        },
+    };
+    let Some(ordering) = ordering else {
+        return false;
+    };
+    match op {
+        Operator::Eq => ordering.is_eq(),
+        Operator::Ne => ordering.is_ne(),
+        Operator::Lt => ordering.is_lt(),
+        Operator::Le => ordering.is_le(),
+        Operator::Gt => ordering.is_gt(),
+        Operator::Ge => ordering.is_ge(),
+    }
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = vec![];
+    let chars = expression.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, length) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('(', _) => (Token::LeftParen, 1),
+            (')', _) => (Token::RightParen, 1),
+            ('=', Some('=')) => (Token::Operator(Operator::Eq), 2),
+            ('!', Some('=')) => (Token::Operator(Operator::Ne), 2),
+            ('<', Some('=')) => (Token::Operator(Operator::Le), 2),
+            ('>', Some('=')) => (Token::Operator(Operator::Ge), 2),
+            ('<', _) => (Token::Operator(Operator::Lt), 1),
+            ('>', _) => (Token::Operator(Operator::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('$', Some('{')) => {
+                let end = chars[i..].iter().position(|c| *c == '}')? + i;
+                let index = chars[i + 2..end].iter().collect::<String>();
+                (Token::Dependency(index.trim().parse().ok()?), end - i + 1)
+            }
+            ('\'' | '"', _) => {
+                let end = chars[i + 1..].iter().position(|q| *q == c)? + i + 1;
+                let literal = chars[i + 1..end].iter().collect::<String>();
+                (Token::Literal(Value::String(literal)), end - i + 1)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let length = chars[i + 1..]
+                    .iter()
+                    .position(|c| !(c.is_ascii_digit() || *c == '.'))
+                    .unwrap_or(chars.len() - i - 1)
+                    + 1;
+                let number = chars[i..i + length].iter().collect::<String>();
+                let number = serde_json::from_str::<serde_json::Number>(&number).ok()?;
+                (Token::Literal(Value::Number(number)), length)
+            }
+            (c, _) if c.is_ascii_alphabetic() => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|c| !c.is_ascii_alphanumeric())
+                    .unwrap_or(chars.len() - i);
+                let literal = match chars[i..i + length].iter().collect::<String>().as_str() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ => return None,
+                };
+                (Token::Literal(literal), length)
+            }
+            _ => return None,
+        };
+        tokens.push(token);
+        i += length;
+    }
+    Some(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next_if(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.position) == Some(token) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Option<Expr> {
+        let mut expr = self.and()?;
+        while self.next_if(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Some(expr)
+    }
+
+    fn and(&mut self) -> Option<Expr> {
+        let mut expr = self.unary()?;
+        while self.next_if(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Some(expr)
+    }
+
+    fn unary(&mut self) -> Option<Expr> {
+        if self.next_if(&Token::Not) {
+            return Some(Expr::Not(Box::new(self.unary()?)));
+        }
+        let left = self.primary()?;
+        match self.tokens.get(self.position) {
+            Some(Token::Operator(op)) => {
+                let op = *op;
+                self.position += 1;
+                Some(Expr::Compare(Box::new(left), op, Box::new(self.primary()?)))
+            }
+            _ => Some(left),
+        }
+    }
+
+    fn primary(&mut self) -> Option<Expr> {
+        if self.next_if(&Token::LeftParen) {
+            let expr = self.or()?;
+            return self.next_if(&Token::RightParen).then_some(expr);
+        }
+        let expr = match self.tokens.get(self.position)? {
+            Token::Literal(v) => Expr::Literal(v.clone()),
+            Token::Dependency(i) => Expr::Dependency(*i),
+            _ => return None,
+        };
+        self.position += 1;
+        Some(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(value: Value) -> Map<String, Value> {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn evaluate_condition_against_record() {
+        let condition = Condition::new("${0} == 'F'", vec!["sex".to_string()]);
+        assert_eq!(
+            condition.evaluate(&record(json!({ "sex": "F" }))),
+            Some(true)
+        );
+        assert_eq!(
+            condition.evaluate(&record(json!({ "sex": "M" }))),
+            Some(false)
+        );
+        assert_eq!(condition.evaluate(&record(json!({}))), Some(false));
+        assert_eq!(condition.describe(), "sex == 'F'");
+    }
+
+    #[test]
+    fn evaluate_logical_and_numeric_condition() {
+        let condition = Condition::new(
+            "(${0} >= 18 && ${1} != \"none\") || !${2}",
+            vec![
+                "age".to_string(),
+                "licence".to_string(),
+                "minor".to_string(),
+            ],
+        );
+        let evaluate = |value| condition.evaluate(&record(value)).unwrap();
+        assert!(evaluate(
+            json!({ "age": 20, "licence": "B", "minor": true })
+        ));
+        assert!(evaluate(
+            json!({ "age": "18", "licence": "B", "minor": true })
+        ));
+        assert!(!evaluate(
+            json!({ "age": 17.5, "licence": "B", "minor": true })
+        ));
+        assert!(!evaluate(
+            json!({ "age": 20, "licence": "none", "minor": true })
+        ));
+        assert!(evaluate(json!({ "age": 10, "minor": false })));
+    }
+
+    #[test]
+    fn invalid_condition_cannot_be_evaluated() {
+        let dependencies = vec!["sex".to_string()];
+        for expression in [
+            "${0} ==",
+            "${1} == 'F'",
+            "sex == 'F'",
+            "(${0}",
+            "${0} = 'F'",
+        ] {
+            let condition = Condition::new(expression, dependencies.clone());
+            assert_eq!(condition.evaluate(&Map::new()), None, "{expression}");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Policy of validating data sets. Missing fields take their default values
+/// when deserialized, so a policy can be loaded from partial JSON, i.e.
+/// `{"strict_types": true, "max_errors": 100}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConstraintsConfig {
+    /// Reports attributes not defined in capture base as errors instead of
+    /// warnings.
+    pub fail_on_additional_attributes: bool,
+    /// Reports columns resolving to an attribute already given by another
+    /// column as errors instead of warnings.
+    pub fail_on_duplicate_headers: bool,
+    /// Requires values of JSON type matching attribute type, i.e. rejects
+    /// numeric string `"23"` of Numeric attribute.
+    pub strict_types: bool,
+    /// Treats strings holding only whitespace as missing values. Otherwise
+    /// only empty strings are missing values.
+    pub whitespace_as_missing: bool,
+    /// Reports missing values of optional attributes as warnings.
+    pub report_empty_values: bool,
+    /// Stops reporting once given number of findings of `Severity::Error` is
+    /// reached.
+    pub max_errors: Option<usize>,
+    /// Matches headers and record keys to attribute names ignoring case.
+    pub case_insensitive_headers: bool,
+    /// Accepts source names from Attribute Mapping overlay of the OCA Bundle
+    /// as headers of attributes they are mapped to.
+    pub allow_mapped_headers: bool,
+}
+
+impl Default for ConstraintsConfig {
+    fn default() -> Self {
+        Self {
+            fail_on_additional_attributes: false,
+            fail_on_duplicate_headers: false,
+            strict_types: false,
+            whitespace_as_missing: true,
+            report_empty_values: false,
+            max_errors: None,
+            case_insensitive_headers: false,
+            allow_mapped_headers: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_partial_policy_with_defaults() {
+        let config: ConstraintsConfig =
+            serde_json::from_str(r#"{ "strict_types": true, "max_errors": 100 }"#).unwrap();
+
+        assert_eq!(
+            config,
+            ConstraintsConfig {
+                strict_types: true,
+                max_errors: Some(100),
+                ..Default::default()
+            }
+        );
+        assert!(config.whitespace_as_missing);
+        assert!(serde_json::from_str::<ConstraintsConfig>(r#"{ "max_errors": -1 }"#).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Severity of validation finding. Only errors make validation fail.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// Machine-readable reason of a validation error.
+///
+/// Serialized with `kind` tag holding snake cased variant name, i.e.
+/// `{"kind": "type_mismatch", "expected_type": "Numeric", "value": "a"}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ValidationErrorKind {
+    /// Mandatory attribute is not present in the record.
+    MissingAttribute,
+    /// Mandatory attribute is present but has no value.
+    MissingValue,
+    /// Optional attribute is present but has no value.
+    EmptyValue,
+    /// Attribute is not defined in capture base.
+    UnknownAttribute,
+    /// Data set has more than one column resolving to the attribute, `header`
+    /// is the repeated one.
+    DuplicateHeader {
+        header: String,
+    },
+    /// Mandatory array attribute has no elements.
+    EmptyArray,
+    TypeMismatch {
+        expected_type: String,
+        value: Value,
+    },
+    FormatMismatch {
+        pattern: String,
+        value: Value,
+    },
+    /// DateTime value is not written in the pattern from Format overlay or
+    /// denotes a date that does not exist.
+    InvalidDateTime {
+        pattern: String,
+        value: Value,
+    },
+    /// Value contains characters outside of the encoding declared in
+    /// Character Encoding overlay or cannot be decoded with it.
+    InvalidEncoding {
+        encoding: String,
+        value: Value,
+    },
+    /// Numeric value is out of range from Format overlay constraints, written
+    /// in interval notation, i.e. `[0, 50]`.
+    OutOfRange {
+        range: String,
+        value: Value,
+    },
+    /// Numeric value has more decimal places than allowed by Format overlay.
+    TooManyDecimals {
+        decimals: usize,
+        value: Value,
+    },
+    /// Number of characters of Text value is out of range from Format
+    /// overlay constraints.
+    LengthOutOfRange {
+        range: String,
+        value: Value,
+    },
+    /// Format overlay defines a pattern which is not a valid regex or
+    /// constraints with invalid values.
+    InvalidFormat {
+        pattern: String,
+    },
+    /// Number of array elements is out of range declared in Cardinality
+    /// overlay.
+    CardinalityMismatch {
+        cardinality: String,
+        count: usize,
+    },
+    InvalidCardinality {
+        cardinality: String,
+    },
+    /// Mandatory attribute is missing while condition from Conditional
+    /// overlay, with dependencies substituted by attribute names, is met.
+    RequiredByCondition {
+        condition: String,
+    },
+    /// Conditional overlay defines an expression which cannot be evaluated.
+    InvalidCondition {
+        condition: String,
+    },
+    EntryCodeNotAllowed {
+        allowed_codes: Vec<String>,
+        value: Value,
+    },
+    /// Entry code is allowed but marked as deprecated with
+    /// `Validator::add_deprecated_entry_codes`.
+    DeprecatedEntryCode {
+        value: Value,
+    },
+    /// Code list referenced by SAID from Entry Code overlay is neither in the
+    /// bundle nor provided by code list provider.
+    UnresolvedCodeList {
+        said: String,
+    },
+    /// OCA of capture base referenced by `Reference:SAID` attribute was not
+    /// added with `Validator::add_reference`, so its value cannot be
+    /// validated.
+    UnresolvedReference {
+        said: String,
+    },
+    /// Data set record could not be loaded.
+    ParseError {
+        message: String,
+    },
+}
+
+impl ValidationErrorKind {
+    /// Returns severity the finding is reported with, unless validator
+    /// configuration says otherwise.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::EmptyValue | Self::DeprecatedEntryCode { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Returns human-readable description of the error for given attribute.
+    pub fn message(&self, attribute_name: &str) -> String {
+        match self {
+            Self::MissingAttribute => "missing_attribute".to_string(),
+            Self::MissingValue => "missing_value".to_string(),
+            Self::EmptyValue => "empty_value".to_string(),
+            Self::UnknownAttribute => "unknown_attribute".to_string(),
+            Self::DuplicateHeader { header } => {
+                format!("'{attribute_name}' is given more than once, again by column '{header}'")
+            }
+            Self::EmptyArray => format!("'{attribute_name}' value ([]) cannot be empty"),
+            Self::TypeMismatch {
+                expected_type,
+                value,
+            } => {
+                if expected_type.eq("Reference") {
+                    format!("'{attribute_name}' value ({value}) must be an object")
+                } else if expected_type.starts_with("Array") {
+                    format!("'{attribute_name}' value ({value}) must be an \"{expected_type}\"")
+                } else {
+                    format!("'{attribute_name}' value ({value}) must be a {expected_type} type")
+                }
+            }
+            Self::FormatMismatch { pattern, value } => format!(
+                "'{attribute_name}' value ({value}) must match defined format ({pattern}) from Format overlay"
+            ),
+            Self::InvalidDateTime { pattern, value } => format!(
+                "'{attribute_name}' value ({value}) must be a valid date/time in format ({pattern})"
+            ),
+            Self::InvalidEncoding { encoding, value } => {
+                format!("'{attribute_name}' value ({value}) must be encoded in {encoding}")
+            }
+            Self::OutOfRange { range, value } => {
+                format!("'{attribute_name}' value ({value}) must be in range {range}")
+            }
+            Self::TooManyDecimals { decimals, value } => format!(
+                "'{attribute_name}' value ({value}) must have at most {decimals} decimal places"
+            ),
+            Self::LengthOutOfRange { range, value } => format!(
+                "'{attribute_name}' value ({value}) length must be in range {range}"
+            ),
+            Self::InvalidFormat { .. } => {
+                format!("'{attribute_name}' format definition is invalid")
+            }
+            Self::CardinalityMismatch { cardinality, count } => format!(
+                "'{attribute_name}' must have {cardinality} elements, has {count}"
+            ),
+            Self::InvalidCardinality { cardinality } => {
+                format!("'{attribute_name}' cardinality definition ({cardinality}) is invalid")
+            }
+            Self::RequiredByCondition { condition } => {
+                format!("'{attribute_name}' is required when {condition}")
+            }
+            Self::InvalidCondition { condition } => {
+                format!("'{attribute_name}' condition ({condition}) is invalid")
+            }
+            Self::EntryCodeNotAllowed {
+                allowed_codes,
+                value,
+            } => format!("'{attribute_name}' value ({value}) must be one of {allowed_codes:?}"),
+            Self::DeprecatedEntryCode { value } => {
+                format!("'{attribute_name}' value ({value}) is deprecated")
+            }
+            Self::UnresolvedCodeList { said } => {
+                format!("'{attribute_name}' code list ({said}) cannot be resolved")
+            }
+            Self::UnresolvedReference { said } => {
+                format!("'{attribute_name}' referenced OCA ({said}) cannot be resolved")
+            }
+            Self::ParseError { message } => message.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_error_kind_with_tag() {
+        let kind = ValidationErrorKind::TypeMismatch {
+            expected_type: "Numeric".to_string(),
+            value: Value::from("a"),
+        };
+        assert_eq!(
+            serde_json::to_value(&kind).unwrap(),
+            serde_json::json!({
+                "kind": "type_mismatch",
+                "expected_type": "Numeric",
+                "value": "a"
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(ValidationErrorKind::MissingAttribute).unwrap(),
+            serde_json::json!({ "kind": "missing_attribute" })
+        );
+    }
+
+    #[test]
+    fn describe_error_kind() {
+        let kind = ValidationErrorKind::EntryCodeNotAllowed {
+            allowed_codes: vec!["op1".to_string(), "op2".to_string()],
+            value: Value::from("op"),
+        };
+        assert_eq!(
+            kind.message("n1"),
+            r#"'n1' value ("op") must be one of ["op1", "op2"]"#
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use serde_json::Value;
+use std::fmt::Display;
+
+use super::ValidationErrorKind;
+
+/// Constraints written in Format overlay as `;` separated clauses following
+/// `constraints:` prefix, i.e. `constraints:min=0;max=50;decimals=2` for
+/// Numeric or `constraints:min_length=3;max_length=10` for Text attribute.
+/// `integer` stands for `decimals=0`. Formats without the prefix are regexes.
+/// Constraints of the other type of attribute make the format invalid.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FormatConstraints {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub decimals: Option<usize>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+}
+
+const PREFIX: &str = "constraints:";
+
+const NUMERIC_KEYS: [&str; 3] = ["min", "max", "decimals"];
+
+const TEXT_KEYS: [&str; 2] = ["min_length", "max_length"];
+
+impl FormatConstraints {
+    /// Returns `None` if format is not written as constraints (i.e. it is a
+    /// regex) and `Some(Err(_))` if constraints are unknown, do not apply to
+    /// Numeric (`numeric`) or Text attribute or have invalid values.
+    pub fn parse(format: &str, numeric: bool) -> Option<Result<Self, String>> {
+        let clauses = format
+            .strip_prefix(PREFIX)?
+            .split(';')
+            .map(|clause| clause.trim())
+            .filter(|clause| !clause.is_empty())
+            .map(|clause| match clause.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (clause, None),
+            })
+            .collect::<Vec<_>>();
+        let is_constraint = |(key, value): &(&str, Option<&str>)| match value {
+            Some(_) => NUMERIC_KEYS.contains(key) || TEXT_KEYS.contains(key),
+            None => key.eq(&"integer"),
+        };
+        let applies = |(key, _): &(&str, Option<&str>)| match numeric {
+            true => !TEXT_KEYS.contains(key),
+            false => TEXT_KEYS.contains(key),
+        };
+        if clauses.is_empty() {
+            return Some(Err("no constraints given".to_string()));
+        }
+        if let Some((key, _)) = clauses.iter().find(|clause| !is_constraint(clause)) {
+            return Some(Err(format!("unknown constraint {key}")));
+        }
+        if let Some((key, _)) = clauses.iter().find(|clause| !applies(clause)) {
+            let attribute_type = if numeric { "Numeric" } else { "Text" };
+            return Some(Err(format!(
+                "constraint {key} does not apply to {attribute_type} attribute"
+            )));
+        }
+        Some(Self::from_clauses(clauses))
+    }
+
+    fn from_clauses(clauses: Vec<(&str, Option<&str>)>) -> Result<Self, String> {
+        let mut constraints = Self::default();
+        for (key, value) in clauses {
+            let invalid = || format!("invalid value of {key} constraint");
+            let value = value.unwrap_or_default();
+            match key {
+                "integer" => constraints.decimals = Some(0),
+                "min" => constraints.min = Some(value.parse().map_err(|_| invalid())?),
+                "max" => constraints.max = Some(value.parse().map_err(|_| invalid())?),
+                "decimals" => constraints.decimals = Some(value.parse().map_err(|_| invalid())?),
+                "min_length" => {
+                    constraints.min_length = Some(value.parse().map_err(|_| invalid())?)
+                }
+                "max_length" => {
+                    constraints.max_length = Some(value.parse().map_err(|_| invalid())?)
+                }
+                _ => unreachable!(),
+            }
+        }
+        Ok(constraints)
+    }
+
+    /// Checks range and decimal places of numeric value written as `text`.
+    pub fn check_number(&self, text: &str, value: &Value) -> Vec<ValidationErrorKind> {
+        let mut errors = vec![];
+        let Ok(number) = text.trim().parse::<f64>() else {
+            return errors;
+        };
+        if self.min.is_some_and(|min| number < min) || self.max.is_some_and(|max| number > max) {
+            errors.push(ValidationErrorKind::OutOfRange {
+                range: describe_range(self.min, self.max),
+                value: value.clone(),
+            });
+        }
+        if let Some(decimals) = self.decimals {
+            if decimal_places(text.trim()) > decimals {
+                errors.push(ValidationErrorKind::TooManyDecimals {
+                    decimals,
+                    value: value.clone(),
+                });
+            }
+        }
+        errors
+    }
+
+    /// Checks number of characters of text value.
+    pub fn check_text(&self, text: &str, value: &Value) -> Vec<ValidationErrorKind> {
+        let length = text.chars().count();
+        if self.min_length.is_some_and(|min| length < min)
+            || self.max_length.is_some_and(|max| length > max)
+        {
+            vec![ValidationErrorKind::LengthOutOfRange {
+                range: describe_range(self.min_length, self.max_length),
+                value: value.clone(),
+            }]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// Describes range in interval notation, i.e. `[0, 50]` or `[3, ∞)`.
+fn describe_range<T: Display>(min: Option<T>, max: Option<T>) -> String {
+    let min = min.map_or("(-∞".to_string(), |min| format!("[{min}"));
+    let max = max.map_or("∞)".to_string(), |max| format!("{max}]"));
+    format!("{min}, {max}")
+}
+
+/// Counts significant decimal places of number written as text, including
+/// exponent notation, i.e. 1 for `5.20` and 4 for `125e-4`.
+fn decimal_places(text: &str) -> usize {
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().unwrap_or(0)),
+        None => (text, 0),
+    };
+    let fraction = mantissa
+        .split_once('.')
+        .map_or("", |(_, fraction)| fraction.trim_end_matches('0'));
+    (fraction.len() as i64 - exponent).max(0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_format_constraints() {
+        assert_eq!(
+            FormatConstraints::parse("constraints:min=0; max=50; decimals=2", true),
+            Some(Ok(FormatConstraints {
+                min: Some(0.),
+                max: Some(50.),
+                decimals: Some(2),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            FormatConstraints::parse("constraints:integer;min=1", true),
+            Some(Ok(FormatConstraints {
+                decimals: Some(0),
+                min: Some(1.),
+                ..Default::default()
+            }))
+        );
+        assert!(matches!(
+            FormatConstraints::parse("constraints:min=zero", true),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            FormatConstraints::parse("constraints:min=0;x=1", true),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            FormatConstraints::parse("constraints:", false),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            FormatConstraints::parse("constraints:min=0;max=50", false),
+            Some(Err(_))
+        ));
+        assert!(matches!(
+            FormatConstraints::parse("constraints:integer;min_length=1", true),
+            Some(Err(_))
+        ));
+        assert_eq!(FormatConstraints::parse("[a-z]+", false), None);
+        assert_eq!(FormatConstraints::parse("min=1", true), None);
+        assert_eq!(FormatConstraints::parse("integer", true), None);
+    }
+
+    #[test]
+    fn check_number_against_constraints() {
+        let constraints = FormatConstraints::parse("constraints:min=0;max=50;decimals=2", true)
+            .unwrap()
+            .unwrap();
+        let check = |text: &str| constraints.check_number(text, &Value::from(text));
+        assert!(check("0").is_empty());
+        assert!(check("49.99").is_empty());
+        assert!(check("5.200").is_empty());
+        assert!(check("125e-2").is_empty());
+        assert_eq!(
+            check("50.5"),
+            vec![ValidationErrorKind::OutOfRange {
+                range: "[0, 50]".to_string(),
+                value: Value::from("50.5"),
+            }]
+        );
+        assert_eq!(
+            check("1.234"),
+            vec![ValidationErrorKind::TooManyDecimals {
+                decimals: 2,
+                value: Value::from("1.234"),
+            }]
+        );
+        assert_eq!(check("-1.234").len(), 2);
+    }
+
+    #[test]
+    fn check_text_length_against_constraints() {
+        let constraints = FormatConstraints::parse("constraints:min_length=2", false)
+            .unwrap()
+            .unwrap();
+        assert!(constraints.check_text("ab", &Value::from("ab")).is_empty());
+        assert_eq!(
+            constraints.check_text("ż", &Value::from("ż")),
+            vec![ValidationErrorKind::LengthOutOfRange {
+                range: "[2, ∞)".to_string(),
+                value: Value::from("ż"),
+            }]
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use oca_rs::state::oca::{overlay, OCA};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::{ValidationError, ValidationErrorKind, Validator};
+
+impl Validator {
+    /// Returns attribute names keyed by their source names from Attribute
+    /// Mapping overlays.
+    pub(super) fn parse_attribute_mappings(oca: &OCA) -> HashMap<String, String> {
+        oca.overlays
+            .iter()
+            .filter(|overlay| overlay.overlay_type().contains("/mapping/"))
+            .filter_map(|overlay| overlay.as_any().downcast_ref::<overlay::AttributeMapping>())
+            .flat_map(|ov| {
+                ov.attribute_mapping
+                    .iter()
+                    .map(|(attr_name, source_name)| (source_name.clone(), attr_name.clone()))
+            })
+            .collect()
+    }
+
+    /// Returns name of attribute given header or record key refers to. Apart
+    /// from attribute names themselves, constraints may allow source names
+    /// from Attribute Mapping overlay and names differing in case. Unknown
+    /// headers are returned as they are.
+    pub(super) fn resolve_header<'a>(&'a self, header: &'a str) -> &'a str {
+        if self.attribute_validators.contains_key(header) {
+            return header;
+        }
+        let config = &self.constraints_config;
+        if config.allow_mapped_headers {
+            if let Some(attr_name) = self.attribute_mappings.get(header) {
+                return attr_name;
+            }
+        }
+        if config.case_insensitive_headers {
+            let header = header.to_lowercase();
+            let attr_name = self
+                .attribute_validators
+                .keys()
+                .find(|attr_name| attr_name.to_lowercase() == header)
+                .or_else(|| {
+                    self.attribute_mappings
+                        .iter()
+                        .filter(|_| config.allow_mapped_headers)
+                        .find(|(source_name, _)| source_name.to_lowercase() == header)
+                        .map(|(_, attr_name)| attr_name)
+                });
+            if let Some(attr_name) = attr_name {
+                return attr_name;
+            }
+        }
+        header
+    }
+
+    /// Renames record keys to names of attributes they resolve to. Key is
+    /// kept when the attribute is already given by another key, so it is
+    /// reported as unknown.
+    pub(super) fn resolve_headers<'a>(
+        &self,
+        record: &'a Map<String, Value>,
+    ) -> Cow<'a, Map<String, Value>> {
+        if record.keys().all(|key| self.resolve_header(key) == key) {
+            return Cow::Borrowed(record);
+        }
+        let mut resolved = Map::new();
+        for (key, value) in record {
+            let attr_name = match self.resolve_header(key) {
+                attr_name if record.contains_key(attr_name) || resolved.contains_key(attr_name) => {
+                    key
+                }
+                attr_name => attr_name,
+            };
+            resolved.insert(attr_name.to_string(), value.clone());
+        }
+        Cow::Owned(resolved)
+    }
+
+    /// Returns attribute types keyed by attribute names, accepted source
+    /// names and given headers resolving to them, so data sets parse values
+    /// of such columns as well.
+    pub(super) fn header_types(&self, headers: Option<&[String]>) -> BTreeMap<String, String> {
+        let mut attribute_types = self.attribute_types.clone();
+        let source_names = self
+            .attribute_mappings
+            .keys()
+            .filter(|_| self.constraints_config.allow_mapped_headers);
+        for header in source_names.chain(headers.unwrap_or_default()) {
+            if let Some(attr_type) = self.attribute_types.get(self.resolve_header(header)) {
+                attribute_types
+                    .entry(header.clone())
+                    .or_insert_with(|| attr_type.clone());
+            }
+        }
+        attribute_types
+    }
+
+    /// Reports headers resolving to an attribute given by a previous one.
+    pub(super) fn header_errors(
+        &self,
+        data_set_index: usize,
+        headers: &[String],
+    ) -> Vec<ValidationError> {
+        let mut attr_names = HashSet::new();
+        headers
+            .iter()
+            .filter(|header| !header.is_empty())
+            .filter_map(|header| {
+                let attr_name = self.resolve_header(header);
+                (!attr_names.insert(attr_name)).then(|| {
+                    self.error(
+                        data_set_index,
+                        "",
+                        attr_name,
+                        attr_name,
+                        ValidationErrorKind::DuplicateHeader {
+                            header: header.clone(),
+                        },
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_set::{CSVDataSet, CSVDataStream, DataSet, JSONDataSet};
+    use crate::validator::{ConstraintsConfig, Severity};
+
+    fn setup_oca() -> OCA {
+        oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": { "email": "Text", "age": "Numeric", "numbers": "Array[Numeric]" },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_mapping": { "email": "e-mail" },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "E4L-BukSBsqZoDDIJvw4_gGjAJs5It4UUfiA200lGup0",
+      "type": "spec/overlays/mapping/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize()
+    }
+
+    #[test]
+    fn resolve_headers_allowed_by_constraints() {
+        let mut validator = Validator::new(setup_oca());
+        let data_set = CSVDataSet::new("E-Mail;AGE;Numbers\na@example.com;24;[1, 2]".to_string());
+
+        let errors = validator.validate_data_set(data_set.as_ref()).errors;
+        assert_eq!(errors.len(), 3);
+        assert!(errors
+            .iter()
+            .all(|e| e.kind == ValidationErrorKind::UnknownAttribute));
+
+        validator.set_constraints(ConstraintsConfig {
+            case_insensitive_headers: true,
+            allow_mapped_headers: true,
+            strict_types: true,
+            ..Default::default()
+        });
+        let result = validator.validate_data_set(data_set.as_ref());
+        assert!(result.success, "{:?}", result.errors);
+        assert!(result.errors.is_empty());
+        assert!(validator
+            .validate_record(&serde_json::json!({ "e-mail": "a@example.com", "Age": 24 }))
+            .errors
+            .is_empty());
+    }
+
+    #[test]
+    fn resolve_headers_of_data_set_and_stream_alike() {
+        let mut validator = Validator::new(setup_oca());
+        validator.set_constraints(ConstraintsConfig {
+            case_insensitive_headers: true,
+            ..Default::default()
+        });
+        let csv = "EMAIL;AGE;Numbers;email\na@example.com;24;[1, 2];b@example.com";
+        let findings = |errors: Vec<ValidationError>| {
+            errors
+                .into_iter()
+                .map(|e| (e.record, e.attribute_name, e.kind))
+                .collect::<Vec<_>>()
+        };
+
+        let data_set_errors = validator
+            .validate_data_set(CSVDataSet::new(csv.to_string()).as_ref())
+            .errors;
+        let mut stream_errors = vec![];
+        validator.validate_stream(&mut CSVDataStream::new(csv.as_bytes()), None, |e| {
+            stream_errors.push(e)
+        });
+
+        assert_eq!(findings(data_set_errors), findings(stream_errors));
+        let mut stream_errors = vec![];
+        validator.validate_stream(&mut CSVDataStream::new(csv.as_bytes()), None, |e| {
+            stream_errors.push(e)
+        });
+        assert_eq!(
+            findings(stream_errors),
+            vec![
+                (
+                    "".to_string(),
+                    "email".to_string(),
+                    ValidationErrorKind::DuplicateHeader {
+                        header: "email".to_string()
+                    }
+                ),
+                (
+                    "0".to_string(),
+                    "EMAIL".to_string(),
+                    ValidationErrorKind::UnknownAttribute
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_duplicate_headers() {
+        let mut validator = Validator::new(setup_oca());
+        validator.set_constraints(ConstraintsConfig {
+            allow_mapped_headers: true,
+            ..Default::default()
+        });
+        let data_set = CSVDataSet::new("email;e-mail\na@example.com;b@example.com".to_string());
+
+        let result = validator.validate_data_set(data_set.as_ref());
+        assert!(result.success);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].attribute_name, "email");
+        assert_eq!(result.errors[0].record, "");
+        assert_eq!(
+            result.errors[0].kind,
+            ValidationErrorKind::DuplicateHeader {
+                header: "e-mail".to_string()
+            }
+        );
+        assert_eq!(result.errors[0].severity, Severity::Warning);
+        assert_eq!(result.errors[1].kind, ValidationErrorKind::UnknownAttribute);
+
+        validator.set_constraints(ConstraintsConfig {
+            allow_mapped_headers: true,
+            fail_on_duplicate_headers: true,
+            ..Default::default()
+        });
+        assert!(!validator.validate_data_set(data_set.as_ref()).success);
+        assert!(
+            validator
+                .validate_data_set(
+                    JSONDataSet::new(r#"{ "email": "a@example.com" }"#.to_string()).as_ref()
+                )
+                .success
+        );
+    }
+}
@@ -0,0 +1,456 @@
+use oca_rs::state::oca::{overlay, OCA};
+use std::collections::BTreeMap;
+
+use super::{ValidationErrorKind, Validator};
+
+/// Message templates of a single locale. Placeholders `{attribute}`,
+/// `{value}`, `{expected_type}`, `{pattern}`, `{encoding}`, `{cardinality}`,
+/// `{count}`, `{condition}`, `{range}`, `{decimals}`, `{codes}`, `{said}`,
+/// `{header}` and `{message}` are substituted when rendering.
+struct Templates {
+    missing_attribute: &'static str,
+    missing_value: &'static str,
+    empty_value: &'static str,
+    unknown_attribute: &'static str,
+    duplicate_header: &'static str,
+    empty_array: &'static str,
+    type_mismatch: &'static str,
+    format_mismatch: &'static str,
+    invalid_date_time: &'static str,
+    invalid_encoding: &'static str,
+    out_of_range: &'static str,
+    too_many_decimals: &'static str,
+    length_out_of_range: &'static str,
+    invalid_format: &'static str,
+    cardinality_mismatch: &'static str,
+    invalid_cardinality: &'static str,
+    required_by_condition: &'static str,
+    invalid_condition: &'static str,
+    entry_code_not_allowed: &'static str,
+    deprecated_entry_code: &'static str,
+    unresolved_code_list: &'static str,
+    unresolved_reference: &'static str,
+    parse_error: &'static str,
+}
+
+const EN: Templates = Templates {
+    missing_attribute: "{attribute} is required",
+    missing_value: "{attribute} must not be empty",
+    empty_value: "{attribute} is empty",
+    unknown_attribute: "{attribute} is not a known field",
+    duplicate_header: "{attribute} is given more than once, again by column {header}",
+    empty_array: "{attribute} must contain at least one value",
+    type_mismatch: "{attribute} has invalid value {value}, expected {expected_type}",
+    format_mismatch: "{attribute} value {value} does not match the required format",
+    invalid_date_time: "{attribute} value {value} is not a valid date/time in format {pattern}",
+    invalid_encoding: "{attribute} value {value} is not valid {encoding} data",
+    out_of_range: "{attribute} value {value} must be in range {range}",
+    too_many_decimals: "{attribute} value {value} must have at most {decimals} decimal places",
+    length_out_of_range: "{attribute} value {value} length must be in range {range}",
+    invalid_format: "{attribute} has invalid format definition",
+    cardinality_mismatch: "{attribute} has {count} values, allowed number of values: {cardinality}",
+    invalid_cardinality: "{attribute} has invalid cardinality definition",
+    required_by_condition: "{attribute} is required when {condition}",
+    invalid_condition: "{attribute} has invalid condition {condition}",
+    entry_code_not_allowed: "{attribute} value {value} is not allowed. Allowed values: {codes}",
+    deprecated_entry_code: "{attribute} value {value} is deprecated",
+    unresolved_code_list: "Allowed values of {attribute} cannot be loaded ({said})",
+    unresolved_reference: "Schema of {attribute} cannot be loaded ({said})",
+    parse_error: "{attribute} could not be read: {message}",
+};
+
+const DE: Templates = Templates {
+    missing_attribute: "{attribute} ist erforderlich",
+    missing_value: "{attribute} darf nicht leer sein",
+    empty_value: "{attribute} ist leer",
+    unknown_attribute: "{attribute} ist kein bekanntes Feld",
+    duplicate_header: "{attribute} ist mehrfach angegeben, erneut in Spalte {header}",
+    empty_array: "{attribute} muss mindestens einen Wert enthalten",
+    type_mismatch: "{attribute} hat einen ungültigen Wert {value}, erwartet wird {expected_type}",
+    format_mismatch: "Der Wert {value} von {attribute} entspricht nicht dem erforderlichen Format",
+    invalid_date_time:
+        "Der Wert {value} von {attribute} ist kein gültiges Datum bzw. keine gültige Uhrzeit im Format {pattern}",
+    invalid_encoding: "Der Wert {value} von {attribute} ist keine gültige {encoding}-Kodierung",
+    out_of_range: "Der Wert {value} von {attribute} muss im Bereich {range} liegen",
+    too_many_decimals:
+        "Der Wert {value} von {attribute} darf höchstens {decimals} Nachkommastellen haben",
+    length_out_of_range: "Die Länge des Wertes {value} von {attribute} muss im Bereich {range} liegen",
+    invalid_format: "{attribute} hat eine ungültige Formatdefinition",
+    cardinality_mismatch: "{attribute} hat {count} Werte, zulässige Anzahl: {cardinality}",
+    invalid_cardinality: "{attribute} hat eine ungültige Kardinalitätsdefinition",
+    required_by_condition: "{attribute} ist erforderlich, wenn {condition}",
+    invalid_condition: "{attribute} hat eine ungültige Bedingung {condition}",
+    entry_code_not_allowed:
+        "Der Wert {value} von {attribute} ist nicht zulässig. Erlaubte Werte: {codes}",
+    deprecated_entry_code: "Der Wert {value} von {attribute} ist veraltet",
+    unresolved_code_list: "Zulässige Werte von {attribute} können nicht geladen werden ({said})",
+    unresolved_reference: "Das Schema von {attribute} kann nicht geladen werden ({said})",
+    parse_error: "{attribute} konnte nicht gelesen werden: {message}",
+};
+
+const FR: Templates = Templates {
+    missing_attribute: "{attribute} est obligatoire",
+    missing_value: "{attribute} ne doit pas être vide",
+    empty_value: "{attribute} est vide",
+    unknown_attribute: "{attribute} n'est pas un champ connu",
+    duplicate_header: "{attribute} est indiqué plusieurs fois, à nouveau par la colonne {header}",
+    empty_array: "{attribute} doit contenir au moins une valeur",
+    type_mismatch: "{attribute} a une valeur invalide {value}, type attendu : {expected_type}",
+    format_mismatch: "La valeur {value} de {attribute} ne respecte pas le format requis",
+    invalid_date_time:
+        "La valeur {value} de {attribute} n'est pas une date/heure valide au format {pattern}",
+    invalid_encoding: "La valeur {value} de {attribute} n'est pas un contenu {encoding} valide",
+    out_of_range: "La valeur {value} de {attribute} doit être dans l'intervalle {range}",
+    too_many_decimals: "La valeur {value} de {attribute} doit avoir au plus {decimals} décimales",
+    length_out_of_range:
+        "La longueur de la valeur {value} de {attribute} doit être dans l'intervalle {range}",
+    invalid_format: "{attribute} a une définition de format invalide",
+    cardinality_mismatch: "{attribute} a {count} valeurs, nombre autorisé : {cardinality}",
+    invalid_cardinality: "{attribute} a une définition de cardinalité invalide",
+    required_by_condition: "{attribute} est obligatoire lorsque {condition}",
+    invalid_condition: "{attribute} a une condition invalide {condition}",
+    entry_code_not_allowed:
+        "La valeur {value} de {attribute} n'est pas autorisée. Valeurs autorisées : {codes}",
+    deprecated_entry_code: "La valeur {value} de {attribute} est obsolète",
+    unresolved_code_list:
+        "Les valeurs autorisées de {attribute} ne peuvent pas être chargées ({said})",
+    unresolved_reference: "Le schéma de {attribute} ne peut pas être chargé ({said})",
+    parse_error: "{attribute} n'a pas pu être lu : {message}",
+};
+
+const PL: Templates = Templates {
+    missing_attribute: "Pole {attribute} jest wymagane",
+    missing_value: "Pole {attribute} nie może być puste",
+    empty_value: "Pole {attribute} jest puste",
+    unknown_attribute: "Nieznane pole {attribute}",
+    duplicate_header: "Pole {attribute} podano więcej niż raz, ponownie w kolumnie {header}",
+    empty_array: "Pole {attribute} musi zawierać co najmniej jedną wartość",
+    type_mismatch:
+        "Pole {attribute} ma nieprawidłową wartość {value}, oczekiwany typ: {expected_type}",
+    format_mismatch: "Wartość {value} pola {attribute} nie jest zgodna z wymaganym formatem",
+    invalid_date_time:
+        "Wartość {value} pola {attribute} nie jest poprawną datą/godziną w formacie {pattern}",
+    invalid_encoding: "Wartość {value} pola {attribute} nie jest poprawnie zakodowana w {encoding}",
+    out_of_range: "Wartość {value} pola {attribute} musi należeć do przedziału {range}",
+    too_many_decimals:
+        "Wartość {value} pola {attribute} może mieć najwyżej {decimals} miejsc po przecinku",
+    length_out_of_range:
+        "Długość wartości {value} pola {attribute} musi należeć do przedziału {range}",
+    invalid_format: "Pole {attribute} ma nieprawidłową definicję formatu",
+    cardinality_mismatch:
+        "Pole {attribute} ma {count} wartości, dozwolona liczba wartości: {cardinality}",
+    invalid_cardinality: "Pole {attribute} ma nieprawidłową definicję krotności",
+    required_by_condition: "Pole {attribute} jest wymagane, gdy {condition}",
+    invalid_condition: "Pole {attribute} ma nieprawidłowy warunek {condition}",
+    entry_code_not_allowed:
+        "Wartość {value} pola {attribute} jest niedozwolona. Dozwolone wartości: {codes}",
+    deprecated_entry_code: "Wartość {value} pola {attribute} jest przestarzała",
+    unresolved_code_list: "Nie można wczytać dozwolonych wartości pola {attribute} ({said})",
+    unresolved_reference: "Nie można wczytać schematu pola {attribute} ({said})",
+    parse_error: "Nie można odczytać pola {attribute}: {message}",
+};
+
+/// Languages with bundled message templates. Other languages fall back to
+/// English templates.
+pub const SUPPORTED_LOCALES: [&str; 4] = ["en", "de", "fr", "pl"];
+
+fn templates(language: &str) -> &'static Templates {
+    match primary_subtag(language).as_str() {
+        "de" => &DE,
+        "fr" => &FR,
+        "pl" => &PL,
+        _ => &EN,
+    }
+}
+
+fn primary_subtag(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Attribute labels and information texts of OCA grouped by language.
+#[derive(Clone)]
+pub struct Localization {
+    labels: BTreeMap<String, BTreeMap<String, String>>,
+    information: BTreeMap<String, BTreeMap<String, String>>,
+    /// SAIDs of capture bases referenced by Reference attributes, keyed by
+    /// attribute name.
+    references: BTreeMap<String, String>,
+    /// Localizations of referenced OCAs keyed by SAID of their capture base.
+    referenced: BTreeMap<String, Localization>,
+}
+
+impl Localization {
+    pub fn new(oca: &OCA) -> Self {
+        let mut labels = BTreeMap::new();
+        let mut information = BTreeMap::new();
+        for overlay in &oca.overlays {
+            let language = match overlay.language() {
+                Some(language) => language.clone(),
+                None => continue,
+            };
+            if overlay.overlay_type().contains("/label/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Label>() {
+                    labels.insert(language, ov.attribute_labels.clone());
+                }
+            } else if overlay.overlay_type().contains("/information/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Information>() {
+                    information.insert(language, ov.attribute_information.clone());
+                }
+            }
+        }
+
+        let references = oca
+            .capture_base
+            .attributes
+            .iter()
+            .filter_map(|(attribute_name, attribute_type)| {
+                let (_, said) = Validator::parse_attribute_type(attribute_type);
+                said.map(|said| (attribute_name.clone(), said))
+            })
+            .collect();
+
+        Self {
+            labels,
+            information,
+            references,
+            referenced: BTreeMap::new(),
+        }
+    }
+
+    /// Adds labels and information texts of OCA referenced by SAID.
+    pub fn add_reference(&mut self, said: &str, oca: &OCA) {
+        self.referenced
+            .insert(said.to_string(), Localization::new(oca));
+    }
+
+    /// Renders message of error referring to attribute at `path` (attribute
+    /// name optionally followed by array element index, i.e. `numbers[1]`,
+    /// or path of attribute of referenced capture base, i.e.
+    /// `performers[1].name`). Each segment of the path is labeled from OCA
+    /// it belongs to.
+    pub fn message(&self, language: &str, kind: &ValidationErrorKind, path: &str) -> String {
+        let mut segments = vec![];
+        let mut information = None;
+        let mut localization = Some(self);
+        for segment in path.split('.') {
+            let (attribute_name, index) =
+                segment.split_at(segment.find('[').unwrap_or(segment.len()));
+            let label = localization.and_then(|l| Self::find(&l.labels, language, attribute_name));
+            segments.push(match label {
+                Some(label) => format!("{}{index}", label.trim()),
+                None => segment.to_string(),
+            });
+            information =
+                localization.and_then(|l| Self::find(&l.information, language, attribute_name));
+            localization = localization
+                .and_then(|l| l.references.get(attribute_name))
+                .and_then(|said| self.referenced.get(said));
+        }
+        let attribute = segments.join(".");
+
+        let templates = templates(language);
+        let (template, params) = match kind {
+            ValidationErrorKind::MissingAttribute => (templates.missing_attribute, vec![]),
+            ValidationErrorKind::MissingValue => (templates.missing_value, vec![]),
+            ValidationErrorKind::EmptyValue => (templates.empty_value, vec![]),
+            ValidationErrorKind::UnknownAttribute => (templates.unknown_attribute, vec![]),
+            ValidationErrorKind::DuplicateHeader { header } => (
+                templates.duplicate_header,
+                vec![("{header}", header.clone())],
+            ),
+            ValidationErrorKind::EmptyArray => (templates.empty_array, vec![]),
+            ValidationErrorKind::TypeMismatch {
+                expected_type,
+                value,
+            } => (
+                templates.type_mismatch,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{expected_type}", expected_type.clone()),
+                ],
+            ),
+            ValidationErrorKind::FormatMismatch { pattern, value } => (
+                templates.format_mismatch,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{pattern}", pattern.clone()),
+                ],
+            ),
+            ValidationErrorKind::InvalidDateTime { pattern, value } => (
+                templates.invalid_date_time,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{pattern}", pattern.clone()),
+                ],
+            ),
+            ValidationErrorKind::InvalidEncoding { encoding, value } => (
+                templates.invalid_encoding,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{encoding}", encoding.clone()),
+                ],
+            ),
+            ValidationErrorKind::OutOfRange { range, value } => (
+                templates.out_of_range,
+                vec![("{value}", value.to_string()), ("{range}", range.clone())],
+            ),
+            ValidationErrorKind::LengthOutOfRange { range, value } => (
+                templates.length_out_of_range,
+                vec![("{value}", value.to_string()), ("{range}", range.clone())],
+            ),
+            ValidationErrorKind::TooManyDecimals { decimals, value } => (
+                templates.too_many_decimals,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{decimals}", decimals.to_string()),
+                ],
+            ),
+            ValidationErrorKind::InvalidFormat { pattern } => (
+                templates.invalid_format,
+                vec![("{pattern}", pattern.clone())],
+            ),
+            ValidationErrorKind::CardinalityMismatch { cardinality, count } => (
+                templates.cardinality_mismatch,
+                vec![
+                    ("{cardinality}", cardinality.clone()),
+                    ("{count}", count.to_string()),
+                ],
+            ),
+            ValidationErrorKind::InvalidCardinality { cardinality } => (
+                templates.invalid_cardinality,
+                vec![("{cardinality}", cardinality.clone())],
+            ),
+            ValidationErrorKind::RequiredByCondition { condition } => (
+                templates.required_by_condition,
+                vec![("{condition}", condition.clone())],
+            ),
+            ValidationErrorKind::InvalidCondition { condition } => (
+                templates.invalid_condition,
+                vec![("{condition}", condition.clone())],
+            ),
+            ValidationErrorKind::EntryCodeNotAllowed {
+                allowed_codes,
+                value,
+            } => (
+                templates.entry_code_not_allowed,
+                vec![
+                    ("{value}", value.to_string()),
+                    ("{codes}", allowed_codes.join(", ")),
+                ],
+            ),
+            ValidationErrorKind::DeprecatedEntryCode { value } => (
+                templates.deprecated_entry_code,
+                vec![("{value}", value.to_string())],
+            ),
+            ValidationErrorKind::UnresolvedCodeList { said } => (
+                templates.unresolved_code_list,
+                vec![("{said}", said.clone())],
+            ),
+            ValidationErrorKind::UnresolvedReference { said } => (
+                templates.unresolved_reference,
+                vec![("{said}", said.clone())],
+            ),
+            ValidationErrorKind::ParseError { message } => {
+                (templates.parse_error, vec![("{message}", message.clone())])
+            }
+        };
+        let message = params.iter().fold(
+            template.replace("{attribute}", &attribute),
+            |message, (placeholder, value)| message.replace(placeholder, value),
+        );
+
+        match information {
+            Some(information) => format!("{message} ({})", information.trim()),
+            None => message,
+        }
+    }
+
+    /// Looks up attribute text in given language, falling back to the one
+    /// matching its primary subtag (i.e. `en` for `en-US`).
+    fn find<'a>(
+        texts: &'a BTreeMap<String, BTreeMap<String, String>>,
+        language: &str,
+        attribute_name: &str,
+    ) -> Option<&'a String> {
+        texts
+            .get(language)
+            .and_then(|t| t.get(attribute_name))
+            .or_else(|| {
+                let primary_subtag = primary_subtag(language);
+                texts
+                    .iter()
+                    .find(|(l, _)| self::primary_subtag(l) == primary_subtag)
+                    .and_then(|(_, t)| t.get(attribute_name))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn setup_oca() -> OCA {
+        let examples_dir_path = format!("{}/../examples", env!("CARGO_MANIFEST_DIR"));
+        let oca_result = oca_zip_resolver::resolve_from_zip(
+            format!("{examples_dir_path}/glucose_records/oca_bundle.zip").as_str(),
+        );
+        assert!(oca_result.is_ok());
+        oca_result.unwrap()
+    }
+
+    #[test]
+    fn render_message_with_label() {
+        let oca = setup_oca();
+        let localization = Localization::new(&oca);
+        let (attribute_name, label) = localization
+            .labels
+            .get("en")
+            .and_then(|labels| labels.iter().next())
+            .unwrap();
+        let information = localization
+            .information
+            .get("en")
+            .and_then(|information| information.get(attribute_name));
+
+        let message = localization.message(
+            "en-GB",
+            &ValidationErrorKind::MissingAttribute,
+            attribute_name,
+        );
+
+        let expected = format!("{} is required", label.trim());
+        match information {
+            Some(information) => {
+                assert_eq!(message, format!("{expected} ({})", information.trim()))
+            }
+            None => assert_eq!(message, expected),
+        }
+    }
+
+    #[test]
+    fn render_message_from_locale_templates() {
+        let localization = Localization::new(&setup_oca());
+        let kind = ValidationErrorKind::EntryCodeNotAllowed {
+            allowed_codes: vec!["A".to_string(), "B".to_string()],
+            value: Value::from("C"),
+        };
+
+        assert_eq!(
+            localization.message("de", &kind, "unknown[0]"),
+            r#"Der Wert "C" von unknown[0] ist nicht zulässig. Erlaubte Werte: A, B"#
+        );
+        assert_eq!(
+            localization.message("pl", &ValidationErrorKind::MissingValue, "unknown"),
+            "Pole unknown nie może być puste"
+        );
+        assert_eq!(
+            localization.message("xx", &ValidationErrorKind::MissingValue, "unknown"),
+            "unknown must not be empty"
+        );
+    }
+}
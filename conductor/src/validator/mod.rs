@@ -1,29 +1,60 @@
-use oca_rs::state::{attribute::AttributeType, entry_codes::EntryCodes, oca::overlay, oca::OCA};
-use regex::Regex;
-use serde_json::Value;
+use oca_rs::state::{
+    attribute::AttributeType, encoding::Encoding, entry_codes::EntryCodes, oca::overlay, oca::OCA,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
 use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 mod attribute_validator;
-use attribute_validator::AttributeValidator;
+use attribute_validator::{AttributeValidator, CompiledFormat};
+mod code_lists;
+mod coercion;
+mod condition;
+mod constraints;
+pub use code_lists::CodeListProvider;
+pub use coercion::CoercedRecord;
+use condition::Condition;
+pub use constraints::ConstraintsConfig;
+mod error_kind;
+mod format_constraints;
+mod headers;
+pub use error_kind::{Severity, ValidationErrorKind};
+mod localization;
+use localization::Localization;
+pub use localization::SUPPORTED_LOCALES;
 
-use crate::data_set::DataSet;
+use crate::data_set::{DataSet, DataStream};
+use crate::errors::GenericError;
+use crate::validation_result::ValidationResult;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ValidationError {
     pub data_set: String,
     pub record: String,
+    /// Path of the attribute without array indices, i.e. `performers.name`
+    /// for `name` of the second element of `performers`. `message` refers to
+    /// the attribute by its full path.
     pub attribute_name: String,
     pub message: String,
+    pub severity: Severity,
+    pub kind: ValidationErrorKind,
 }
 
 impl ValidationError {
-    pub fn new(data_set: String, record: String, attribute_name: String, message: String) -> Self {
+    pub fn new(
+        data_set: String,
+        record: String,
+        attribute_name: String,
+        kind: ValidationErrorKind,
+    ) -> Self {
         Self {
+            message: kind.message(&attribute_name),
+            severity: kind.severity(),
             data_set,
             record,
             attribute_name,
-            message,
+            kind,
         }
     }
 }
@@ -39,30 +70,99 @@ impl std::fmt::Display for ValidationError {
     }
 }
 
+#[derive(Clone)]
 pub struct Validator {
     pub data_sets: Vec<Box<dyn DataSet + Sync + Send>>,
-    constraints_config: Option<Arc<ConstraintsConfig>>,
+    constraints_config: ConstraintsConfig,
     attribute_validators: HashMap<String, AttributeValidator>,
+    /// Attribute validators of referenced capture bases keyed by SAID.
+    references: HashMap<String, HashMap<String, AttributeValidator>>,
+    code_lists: HashMap<String, Vec<String>>,
+    /// Attribute names keyed by their source names from Attribute Mapping
+    /// overlay.
+    attribute_mappings: HashMap<String, String>,
+    /// Entry codes reported as deprecated, keyed by attribute name.
+    deprecated_entry_codes: HashMap<String, Vec<String>>,
+    code_list_provider: Option<Arc<dyn CodeListProvider + Sync + Send>>,
     attribute_types: BTreeMap<String, String>,
+    localization: Localization,
+    language: Option<String>,
 }
 
-#[derive(Clone)]
-pub struct ConstraintsConfig {
-    pub fail_on_additional_attributes: bool,
-}
+/// Record of data set or stream, or errors of loading it.
+type LoadedRecord = Result<Value, Vec<GenericError>>;
+
+/// Number of records validated by a single task in parallel mode.
+#[cfg(feature = "parallel")]
+const PARALLEL_CHUNK_SIZE: usize = 256;
 
 impl Validator {
     pub fn new(oca: OCA) -> Self {
         Self {
             data_sets: vec![],
-            constraints_config: None,
+            constraints_config: ConstraintsConfig::default(),
             attribute_validators: Self::parse_oca_attributes_to_validators(&oca),
+            attribute_mappings: Self::parse_attribute_mappings(&oca),
+            references: HashMap::new(),
+            code_lists: HashMap::new(),
+            deprecated_entry_codes: HashMap::new(),
+            code_list_provider: None,
             attribute_types: oca.capture_base.attributes.clone(),
+            localization: Localization::new(&oca),
+            language: None,
         }
     }
 
     pub fn set_constraints(&mut self, config: ConstraintsConfig) {
-        self.constraints_config = Some(Arc::new(config));
+        self.constraints_config = config;
+    }
+
+    /// Sets language of validation error messages. Attributes are referred to
+    /// by labels from Label overlay in given language and Information overlay
+    /// texts are appended. Messages are rendered from English templates when
+    /// the language is not one of `SUPPORTED_LOCALES`.
+    pub fn set_language(&mut self, language: &str) {
+        self.language = Some(language.to_string());
+    }
+
+    /// Adds OCA of capture base referenced by `Reference:SAID` or
+    /// `Array[Reference:SAID]` attributes. Values of such attributes are
+    /// validated against rules of the referenced OCA.
+    pub fn add_reference(&mut self, said: &str, oca: OCA) -> &mut Self {
+        self.references.insert(
+            said.to_string(),
+            Self::parse_oca_attributes_to_validators(&oca),
+        );
+        self.localization.add_reference(said, &oca);
+        self
+    }
+
+    /// Adds code list referenced by SAID from Entry Code overlay.
+    pub fn add_code_list(&mut self, said: &str, codes: Vec<String>) -> &mut Self {
+        self.code_lists.insert(said.to_string(), codes);
+        self
+    }
+
+    /// Marks entry codes of given attribute as deprecated. Such values are
+    /// still valid but reported as warnings. Attributes of referenced capture
+    /// bases are given by their path without array indices, i.e.
+    /// `subject.gender` or `performers.gender`.
+    pub fn add_deprecated_entry_codes(
+        &mut self,
+        attribute_name: &str,
+        codes: Vec<String>,
+    ) -> &mut Self {
+        self.deprecated_entry_codes
+            .entry(attribute_name.to_string())
+            .or_default()
+            .extend(codes);
+        self
+    }
+
+    /// Sets provider of code lists referenced by SAID from Entry Code overlay
+    /// which were not added with `add_code_list`.
+    pub fn set_code_list_provider(&mut self, provider: Box<dyn CodeListProvider + Sync + Send>) {
+        self.code_list_provider = Some(Arc::from(provider));
     }
 
     pub fn add_data_set(&mut self, data_set: Box<dyn DataSet + Sync + Send>) -> &mut Self {
@@ -70,12 +170,266 @@ impl Validator {
         self
     }
 
-    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
-        let mut validation_errors: Vec<ValidationError> = vec![];
+    /// Validates records of all data sets added with `add_data_set`.
+    pub fn validate(&self) -> ValidationResult {
+        self.result(
+            self.data_sets
+                .iter()
+                .enumerate()
+                .flat_map(|(data_set_index, data_set)| {
+                    self.data_set_errors(data_set_index, data_set.as_ref())
+                })
+                .collect(),
+        )
+    }
+
+    /// Validates records of given data set without adding it to the
+    /// validator, so repeated calls neither accumulate records nor report
+    /// errors of previous calls. Errors refer to data set `0`.
+    pub fn validate_data_set(&self, data_set: &dyn DataSet) -> ValidationResult {
+        self.result(self.data_set_errors(0, data_set))
+    }
+
+    /// Validates single record, i.e. object with values keyed by attribute
+    /// names. Errors refer to data set `0` and record `0`, apart from invalid
+    /// conditions of the OCA, which refer to no record.
+    pub fn validate_record(&self, record: &Value) -> ValidationResult {
+        let mut errors = self.condition_errors(0);
+        errors.extend(self.record_errors(0, 0, record));
+        self.result(errors)
+    }
+
+    /// Collects findings into result, dropping the ones following
+    /// `max_errors` errors from constraints.
+    fn result(&self, mut errors: Vec<ValidationError>) -> ValidationResult {
+        if let Some(max) = self.constraints_config.max_errors {
+            let mut count = 0;
+            if let Some(position) = errors.iter().position(|e| {
+                if e.severity == Severity::Error {
+                    count += 1;
+                }
+                count > max
+            }) {
+                errors.truncate(position);
+            }
+        }
+        ValidationResult::from(errors)
+    }
+
+    fn data_set_errors(
+        &self,
+        data_set_index: usize,
+        data_set: &dyn DataSet,
+    ) -> Vec<ValidationError> {
+        let headers = data_set.headers();
+        let mut errors = self.header_errors(data_set_index, headers.as_deref().unwrap_or_default());
+        errors.extend(self.condition_errors(data_set_index));
+        match data_set.load_records(self.header_types(headers.as_deref())) {
+            Ok(records) => errors.extend(self.records_errors(data_set_index, &records)),
+            Err(load_errors) => errors.extend(
+                load_errors
+                    .iter()
+                    .map(|e| self.parse_error(data_set_index, "", e)),
+            ),
+        }
+        errors
+    }
+
+    /// Validates records of data stream one by one and passes their findings
+    /// to `on_error` as soon as they are found, so memory usage does not
+    /// depend on size of the input. Reading stops once `max_errors` findings
+    /// of `Severity::Error` are reported, `max_errors` from constraints is
+    /// used when not given. Findings refer to data set `0`. Returns number of
+    /// reported errors.
+    pub fn validate_stream(
+        &self,
+        stream: &mut dyn DataStream,
+        max_errors: Option<usize>,
+        mut on_error: impl FnMut(ValidationError),
+    ) -> usize {
+        let max_errors = max_errors.or(self.constraints_config.max_errors);
+        let headers = stream.headers();
+        let attribute_types = self.header_types(headers.as_deref());
+        let mut errors = self.header_errors(0, headers.as_deref().unwrap_or_default());
+        errors.extend(self.condition_errors(0));
+        let mut count = 0;
+        let mut record_index = 0;
+        loop {
+            for error in errors {
+                if max_errors.is_some_and(|max| count >= max) {
+                    break;
+                }
+                if error.severity == Severity::Error {
+                    count += 1;
+                }
+                on_error(error);
+            }
+            if max_errors.is_some_and(|max| count >= max) {
+                break;
+            }
+            errors = match stream.next_record(&attribute_types) {
+                Some(record) => self.loaded_record_errors(0, record_index, &record),
+                None => break,
+            };
+            record_index += 1;
+        }
+        count
+    }
+
+    /// Builds error of data set or record which could not be loaded. Errors
+    /// of values are prefixed with attribute name, i.e. `number: invalid
+    /// float literal`.
+    fn parse_error(
+        &self,
+        data_set_index: usize,
+        record: &str,
+        error: &GenericError,
+    ) -> ValidationError {
+        let error = error.to_string();
+        let (attribute_name, message) = match error.split_once(": ") {
+            Some((attribute_name, message))
+                if self.attribute_types.contains_key(attribute_name) =>
+            {
+                (attribute_name, message)
+            }
+            _ => ("", error.as_str()),
+        };
+        self.error(
+            data_set_index,
+            record,
+            attribute_name,
+            attribute_name,
+            ValidationErrorKind::ParseError {
+                message: message.to_string(),
+            },
+        )
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    fn records_errors(
+        &self,
+        data_set_index: usize,
+        records: &[LoadedRecord],
+    ) -> Vec<ValidationError> {
+        records
+            .iter()
+            .enumerate()
+            .flat_map(|(record_index, record)| {
+                self.loaded_record_errors(data_set_index, record_index, record)
+            })
+            .collect()
+    }
+
+    /// Validates chunks of records concurrently. Errors are ordered by record
+    /// index as in sequential validation.
+    #[cfg(feature = "parallel")]
+    fn records_errors(
+        &self,
+        data_set_index: usize,
+        records: &[LoadedRecord],
+    ) -> Vec<ValidationError> {
+        use rayon::prelude::*;
+
+        records
+            .par_chunks(PARALLEL_CHUNK_SIZE)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(i, record)| {
+                        self.loaded_record_errors(
+                            data_set_index,
+                            chunk_index * PARALLEL_CHUNK_SIZE + i,
+                            record,
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Validates loaded record or reports errors of loading it.
+    fn loaded_record_errors(
+        &self,
+        data_set_index: usize,
+        record_index: usize,
+        record: &LoadedRecord,
+    ) -> Vec<ValidationError> {
+        match record {
+            Ok(record) => self.record_errors(data_set_index, record_index, record),
+            Err(errors) => errors
+                .iter()
+                .map(|e| self.parse_error(data_set_index, &record_index.to_string(), e))
+                .collect(),
+        }
+    }
+
+    fn record_errors(
+        &self,
+        data_set_index: usize,
+        record_index: usize,
+        record: &Value,
+    ) -> Vec<ValidationError> {
+        let record_index = record_index.to_string();
+        let Some(record) = record.as_object() else {
+            return vec![self.error(
+                data_set_index,
+                &record_index,
+                "",
+                "",
+                ValidationErrorKind::ParseError {
+                    message: "record must be an object".to_string(),
+                },
+            )];
+        };
+        self.validate_attributes(
+            &self.resolve_headers(record),
+            &self.attribute_validators,
+            "",
+        )
+        .into_iter()
+        .map(|(path, kind)| {
+            self.error(
+                data_set_index,
+                &record_index,
+                &Self::unindexed_path(&path),
+                &path,
+                kind,
+            )
+        })
+        .collect()
+    }
+
+    /// Returns errors of record attributes paired with path of the attribute
+    /// they refer to, relative to the record. `parent` is path of the record
+    /// itself, empty for records of data sets.
+    fn validate_attributes(
+        &self,
+        record: &Map<String, Value>,
+        attribute_validators: &HashMap<String, AttributeValidator>,
+        parent: &str,
+    ) -> Vec<(String, ValidationErrorKind)> {
+        let mut errors = vec![];
+
+        // Attributes are optional when condition from Conditional overlay is
+        // not met, so they are not required but their values are validated.
+        // Invalid conditions are reported once per data set and attributes
+        // keep their conformance.
+        let mut optional_attribute_names = vec![];
+        for (attr_name, validator) in attribute_validators {
+            let condition = validator.condition.as_ref();
+            if condition.and_then(|c| c.evaluate(record)) == Some(false) {
+                optional_attribute_names.push(attr_name);
+            }
+        }
 
-        let mandatory_attribute_names = self
-            .attribute_validators
+        let mut missing_attribute_names = attribute_validators
             .iter()
+            .filter(|(attr_name, _)| !optional_attribute_names.contains(attr_name))
             .filter(|(_, v)| {
                 if let Some(conformance) = &v.conformance {
                     conformance.eq("M")
@@ -85,178 +439,307 @@ impl Validator {
             })
             .map(|(attr_name, _)| attr_name)
             .collect::<Vec<&String>>();
+        missing_attribute_names.sort();
 
-        for (data_set_index, data_set) in self.data_sets.iter().enumerate() {
-            for (record_index, record) in data_set
-                .load(self.attribute_types.clone())
-                .map_err(|errors| {
-                    errors
-                        .iter()
-                        .map(|e| {
-                            let e_str = e.to_string();
-                            let splitted: Vec<&str> = e_str.split(": ").collect();
-                            let empty_str = "";
-                            ValidationError::new(
-                                data_set_index.to_string(),
-                                "".to_string(),
-                                splitted.get(0).unwrap_or(&empty_str).to_string(),
-                                splitted.get(1).unwrap_or(&empty_str).to_string(),
-                            )
-                        })
-                        .collect::<Vec<ValidationError>>()
-                })?
-                .iter()
-                .enumerate()
-            {
-                let mut missing_attribute_names = mandatory_attribute_names.clone();
-                for (k, v) in record.as_object().unwrap().iter() {
-                    missing_attribute_names.retain(|n| n.ne(&k));
-
-                    let attribute_validator = self.attribute_validators.get(k).ok_or_else(|| {
-                        ValidationError::new(
-                            data_set_index.to_string(),
-                            record_index.to_string(),
-                            k.to_string(),
-                            "unknown_attribute".to_string(),
-                        )
-                    });
-                    match attribute_validator {
-                        Ok(validator) => {
-                            if let Err(errors) = Validator::validate_value(v, validator) {
-                                for error in errors {
-                                    validation_errors.push(ValidationError::new(
-                                        data_set_index.to_string(),
-                                        record_index.to_string(),
-                                        k.to_string(),
-                                        error,
-                                    ));
+        for (k, v) in record.iter() {
+            missing_attribute_names.retain(|n| n.ne(&k));
+
+            match attribute_validators.get(k) {
+                Some(validator) => {
+                    let optional = optional_attribute_names.contains(&k);
+                    if let Err(value_errors) = self.validate_value(v, validator, parent) {
+                        errors.extend(value_errors.into_iter().filter_map(|(path, kind)| {
+                            match (&validator.condition, kind) {
+                                (_, ValidationErrorKind::MissingValue)
+                                    if path.eq(k) && optional =>
+                                {
+                                    self.constraints_config
+                                        .report_empty_values
+                                        .then_some((path, ValidationErrorKind::EmptyValue))
                                 }
-                            }
-                        }
-                        Err(err) => {
-                            if let Some(config) = &self.constraints_config {
-                                if config.fail_on_additional_attributes {
-                                    validation_errors.push(err);
+                                (Some(condition), ValidationErrorKind::MissingValue)
+                                    if path.eq(k) && condition.is_valid() =>
+                                {
+                                    Some((path, Self::required_by(condition)))
                                 }
+                                (_, kind) => Some((path, kind)),
                             }
-                            continue;
-                        }
+                        }));
                     }
                 }
-                for missing_attribute_name in missing_attribute_names {
-                    validation_errors.push(ValidationError::new(
-                        data_set_index.to_string(),
-                        record_index.to_string(),
-                        missing_attribute_name.to_string(),
-                        "missing_attribute".to_string(),
-                    ));
+                None => errors.push((k.to_string(), ValidationErrorKind::UnknownAttribute)),
+            }
+        }
+        for missing_attribute_name in missing_attribute_names {
+            let kind = match &attribute_validators[missing_attribute_name].condition {
+                Some(condition) if condition.is_valid() => Self::required_by(condition),
+                _ => ValidationErrorKind::MissingAttribute,
+            };
+            errors.push((missing_attribute_name.to_string(), kind));
+        }
+
+        errors
+    }
+
+    /// Reports conditions from Conditional overlays of the OCA and referenced
+    /// ones which cannot be evaluated. Attributes with such conditions are
+    /// validated as if they had none.
+    fn condition_errors(&self, data_set_index: usize) -> Vec<ValidationError> {
+        let mut invalid_conditions = vec![];
+        self.collect_invalid_conditions(
+            "",
+            &self.attribute_validators,
+            &mut vec![],
+            &mut invalid_conditions,
+        );
+        invalid_conditions
+            .into_iter()
+            .map(|(path, condition)| {
+                self.error(
+                    data_set_index,
+                    "",
+                    &Self::unindexed_path(&path),
+                    &path,
+                    ValidationErrorKind::InvalidCondition { condition },
+                )
+            })
+            .collect()
+    }
+
+    fn collect_invalid_conditions<'a>(
+        &'a self,
+        prefix: &str,
+        attribute_validators: &'a HashMap<String, AttributeValidator>,
+        visited: &mut Vec<&'a String>,
+        invalid_conditions: &mut Vec<(String, String)>,
+    ) {
+        let mut attr_names = attribute_validators.keys().collect::<Vec<_>>();
+        attr_names.sort();
+        for attr_name in attr_names {
+            let validator = &attribute_validators[attr_name];
+            let path = format!("{prefix}{attr_name}");
+            if let Some(condition) = validator.condition.as_ref().filter(|c| !c.is_valid()) {
+                invalid_conditions.push((path.clone(), condition.expression().to_string()));
+            }
+            if let (Some(said), Ok(Some(validators))) =
+                (&validator.reference, self.referenced_validators(validator))
+            {
+                if !visited.contains(&said) {
+                    visited.push(said);
+                    self.collect_invalid_conditions(
+                        &format!("{path}."),
+                        validators,
+                        visited,
+                        invalid_conditions,
+                    );
+                    visited.pop();
                 }
             }
         }
+    }
 
-        if validation_errors.is_empty() {
-            Ok(())
+    fn required_by(condition: &Condition) -> ValidationErrorKind {
+        ValidationErrorKind::RequiredByCondition {
+            condition: condition.describe(),
+        }
+    }
+
+    /// Returns path of attribute nested in record at `parent` path.
+    fn join_path(parent: &str, path: &str) -> String {
+        if parent.is_empty() {
+            path.to_string()
         } else {
-            Err(validation_errors)
+            format!("{parent}.{path}")
+        }
+    }
+
+    /// Returns path with indices of array elements removed, i.e.
+    /// `performers.name` for `performers[1].name`.
+    fn unindexed_path(path: &str) -> String {
+        let mut unindexed = String::with_capacity(path.len());
+        let mut in_index = false;
+        for c in path.chars() {
+            match c {
+                '[' => in_index = true,
+                ']' => in_index = false,
+                c if !in_index => unindexed.push(c),
+                _ => {}
+            }
+        }
+        unindexed
+    }
+
+    /// Builds error with message describing attribute at `path`, localised
+    /// when language is set.
+    fn error(
+        &self,
+        data_set_index: usize,
+        record: &str,
+        attribute_name: &str,
+        path: &str,
+        kind: ValidationErrorKind,
+    ) -> ValidationError {
+        let mut error = ValidationError::new(
+            data_set_index.to_string(),
+            record.to_string(),
+            attribute_name.to_string(),
+            kind,
+        );
+        error.message = match &self.language {
+            Some(language) => self.localization.message(language, &error.kind, path),
+            None => error.kind.message(path),
+        };
+        let downgrade = match error.kind {
+            ValidationErrorKind::UnknownAttribute => {
+                !self.constraints_config.fail_on_additional_attributes
+            }
+            ValidationErrorKind::DuplicateHeader { .. } => {
+                !self.constraints_config.fail_on_duplicate_headers
+            }
+            _ => false,
+        };
+        if downgrade {
+            error.severity = Severity::Warning;
         }
+        error
     }
 
-    fn validate_value(value: &Value, validator: &AttributeValidator) -> Result<(), Vec<String>> {
+    /// Returns validators of attributes of OCA referenced by the attribute,
+    /// `None` when it refers to no SAID. SAID of OCA which was not added is
+    /// returned as `Err`.
+    fn referenced_validators<'a>(
+        &'a self,
+        validator: &'a AttributeValidator,
+    ) -> Result<Option<&'a HashMap<String, AttributeValidator>>, &'a String> {
+        match &validator.reference {
+            Some(said) => self.references.get(said).map(Some).ok_or(said),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns errors of given value paired with path of the attribute
+    /// (including array element index and attributes of referenced capture
+    /// base, i.e. `subject.name`) they refer to, relative to the record at
+    /// `parent` path.
+    fn validate_value(
+        &self,
+        value: &Value,
+        validator: &AttributeValidator,
+        parent: &str,
+    ) -> Result<(), Vec<(String, ValidationErrorKind)>> {
         let mut errors = vec![];
+        let path = validator.attribute_name.clone();
 
-        if let Some(ref conformance) = validator.conformance {
-            if conformance.eq("M") {
-                match value {
-                    Value::Null => {
-                        errors.push("missing_value".to_string());
-                    }
-                    Value::String(v) => {
-                        if v.trim().is_empty() {
-                            errors.push("missing_value".to_string());
-                        }
-                    }
-                    _ => {}
+        let is_empty = match value {
+            Value::Null => true,
+            Value::String(v) if self.constraints_config.whitespace_as_missing => {
+                v.trim().is_empty()
+            }
+            Value::String(v) => v.is_empty(),
+            _ => false,
+        };
+        if is_empty {
+            match validator.conformance.as_deref() {
+                Some("M") => errors.push((path.clone(), ValidationErrorKind::MissingValue)),
+                _ if self.constraints_config.report_empty_values => {
+                    errors.push((path.clone(), ValidationErrorKind::EmptyValue))
                 }
+                _ => {}
             }
         }
 
-        if !value.is_null() {
+        if !is_empty {
+            let type_mismatch = || {
+                (
+                    path.clone(),
+                    ValidationErrorKind::TypeMismatch {
+                        expected_type: serde_json::to_value(validator.attribute_type)
+                            .ok()
+                            .and_then(|t| t.as_str().map(|t| t.to_string()))
+                            .unwrap_or_default(),
+                        value: value.clone(),
+                    },
+                )
+            };
             match validator.attribute_type {
                 AttributeType::Text => {
                     if !value.is_string() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be a Text type",
-                            validator.attribute_name, value
+                        errors.push(type_mismatch());
+                    } else {
+                        errors.extend(Self::validate_format(
+                            &path,
+                            value.as_str().unwrap(),
+                            value,
+                            validator,
+                            false,
                         ));
-                    } else if let Some(ref format) = validator.format {
-                        let regex = Regex::new(format!("^{}$", format).as_str());
-                        match regex {
-                            Ok(re) => {
-                                if !re.is_match(value.as_str().unwrap()) {
-                                    errors.push(format!(
-                                        "'{}' value ({}) must match defined format ({}) from Format overlay",
-                                        validator.attribute_name, value, format
-                                    ));
-                                }
-                            }
-                            Err(_) => {
-                                errors.push(format!(
-                                    "'{}' format definition is invalid",
-                                    validator.attribute_name
-                                ));
-                            }
-                        }
                     }
                 }
-                AttributeType::Numeric => match value {
-                    Value::Number(_) => {}
-                    Value::String(v) => {
-                        if let Err(_err) = v.parse::<f64>() {
-                            errors.push(format!(
-                                "'{}' value ({}) must be a Numeric type",
-                                validator.attribute_name, value
-                            ));
+                AttributeType::Numeric => {
+                    let text = match value {
+                        Value::Number(n) => Some(n.to_string()),
+                        Value::String(v)
+                            if !self.constraints_config.strict_types
+                                && v.parse::<f64>().is_ok() =>
+                        {
+                            Some(v.clone())
                         }
+                        _ => None,
+                    };
+                    match text {
+                        Some(text) => errors
+                            .extend(Self::validate_format(&path, &text, value, validator, true)),
+                        None => errors.push(type_mismatch()),
                     }
-                    _ => {
-                        errors.push(format!(
-                            "'{}' value ({}) must be a Numeric type",
-                            validator.attribute_name, value
-                        ));
-                    }
-                },
+                }
                 AttributeType::Boolean => {
                     if !value.is_boolean() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be a Boolean type",
-                            validator.attribute_name, value
-                        ));
+                        errors.push(type_mismatch());
                     }
                 }
                 AttributeType::DateTime => {
                     if !value.is_string() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be a DateTime type",
-                            validator.attribute_name, value
-                        ));
+                        errors.push(type_mismatch());
+                    } else if let Some(CompiledFormat::DateTime(format)) =
+                        &validator.compiled_format
+                    {
+                        if !format.matches(value.as_str().unwrap()) {
+                            errors.push((
+                                path.clone(),
+                                ValidationErrorKind::InvalidDateTime {
+                                    pattern: format.pattern().to_string(),
+                                    value: value.clone(),
+                                },
+                            ));
+                        }
                     }
                 }
                 AttributeType::Binary => {
                     if !value.is_string() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be a Binary type",
-                            validator.attribute_name, value
-                        ));
+                        errors.push(type_mismatch());
                     }
                 }
-                AttributeType::Reference => {
-                    if !value.is_object() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be an object",
-                            validator.attribute_name, value
-                        ));
+                AttributeType::Reference => match value {
+                    Value::Object(record) => match self.referenced_validators(validator) {
+                        Ok(Some(attribute_validators)) => {
+                            for (nested_path, kind) in self.validate_attributes(
+                                record,
+                                attribute_validators,
+                                &Self::join_path(parent, &path),
+                            ) {
+                                errors.push((format!("{path}.{nested_path}"), kind));
+                            }
+                        }
+                        Ok(None) => {}
+                        Err(said) => errors.push((
+                            path.clone(),
+                            ValidationErrorKind::UnresolvedReference { said: said.clone() },
+                        )),
+                    },
+                    _ => {
+                        errors.push(type_mismatch());
                     }
-                }
+                },
                 AttributeType::ArrayText
                 | AttributeType::ArrayNumeric
                 | AttributeType::ArrayBoolean
@@ -264,27 +747,43 @@ impl Validator {
                 | AttributeType::ArrayBinary
                 | AttributeType::ArrayReference => {
                     if !value.is_array() {
-                        errors.push(format!(
-                            "'{}' value ({}) must be an {}",
-                            validator.attribute_name,
-                            value,
-                            serde_json::to_string(&validator.attribute_type).unwrap()
-                        ));
+                        errors.push(type_mismatch());
                     } else {
                         let value_elements = value.as_array().unwrap();
+                        if let Some(ref cardinality) = validator.cardinality {
+                            match Self::parse_cardinality(cardinality) {
+                                Some((min, max)) => {
+                                    let count = value_elements.len();
+                                    if count < min || max.is_some_and(|max| count > max) {
+                                        errors.push((
+                                            path.clone(),
+                                            ValidationErrorKind::CardinalityMismatch {
+                                                cardinality: cardinality.clone(),
+                                                count,
+                                            },
+                                        ));
+                                    }
+                                }
+                                None => {
+                                    errors.push((
+                                        path.clone(),
+                                        ValidationErrorKind::InvalidCardinality {
+                                            cardinality: cardinality.clone(),
+                                        },
+                                    ));
+                                }
+                            }
+                        }
                         if value_elements.is_empty() {
                             if let Some(conformance) = &validator.conformance {
                                 if conformance.eq("M") {
-                                    errors.push(format!(
-                                        "'{}' value ({}) cannot be empty",
-                                        validator.attribute_name, value
-                                    ));
+                                    errors.push((path.clone(), ValidationErrorKind::EmptyArray));
                                 }
                             }
                         }
                         for (i, element) in value_elements.iter().enumerate() {
                             if let Err(errs) =
-                                Validator::validate_value(element, &validator.element(i).unwrap())
+                                self.validate_value(element, &validator.element(i).unwrap(), parent)
                             {
                                 errors.extend(errs);
                             }
@@ -293,14 +792,68 @@ impl Validator {
                 }
             }
 
-            if let Some(EntryCodes::Array(ref codes)) = validator.entry_codes {
-                if value.is_string() && !codes.contains(&value.as_str().unwrap().to_string()) {
-                    errors.push(format!(
-                        "'{}' value ({}) must be one of {:?}",
-                        validator.attribute_name, value, codes
+            if let (Some(encoding), Some(v)) = (validator.encoding, value.as_str()) {
+                if matches!(
+                    validator.attribute_type,
+                    AttributeType::Text | AttributeType::Binary
+                ) && !Validator::is_encoded(v, encoding)
+                {
+                    errors.push((
+                        path.clone(),
+                        ValidationErrorKind::InvalidEncoding {
+                            encoding: serde_json::to_value(encoding)
+                                .ok()
+                                .and_then(|e| e.as_str().map(|e| e.to_string()))
+                                .unwrap_or_default(),
+                            value: value.clone(),
+                        },
                     ));
                 }
             }
+
+            let code = match value {
+                Value::String(v) => Some(v.clone()),
+                Value::Number(v) => Some(v.to_string()),
+                _ => None,
+            };
+            if let (Some(entry_codes), Some(code)) = (&validator.entry_codes, code) {
+                let codes = match entry_codes {
+                    EntryCodes::Array(codes) => Some(codes.clone()),
+                    EntryCodes::Sai(said) => self.code_list(said),
+                };
+                match codes {
+                    Some(codes) => {
+                        if !codes.contains(&code) {
+                            errors.push((
+                                path,
+                                ValidationErrorKind::EntryCodeNotAllowed {
+                                    allowed_codes: codes,
+                                    value: value.clone(),
+                                },
+                            ));
+                        } else if self
+                            .deprecated_entry_codes
+                            .get(&Self::unindexed_path(&Self::join_path(parent, &path)))
+                            .is_some_and(|deprecated| deprecated.contains(&code))
+                        {
+                            errors.push((
+                                path,
+                                ValidationErrorKind::DeprecatedEntryCode {
+                                    value: value.clone(),
+                                },
+                            ));
+                        }
+                    }
+                    None => {
+                        if let EntryCodes::Sai(said) = entry_codes {
+                            errors.push((
+                                path,
+                                ValidationErrorKind::UnresolvedCodeList { said: said.clone() },
+                            ));
+                        }
+                    }
+                }
+            }
         }
 
         if errors.is_empty() {
@@ -310,14 +863,119 @@ impl Validator {
         }
     }
 
+    /// Checks text of Text or Numeric value against Format overlay, written
+    /// either as constraints (see `FormatConstraints`) or as regex.
+    fn validate_format(
+        path: &str,
+        text: &str,
+        value: &Value,
+        validator: &AttributeValidator,
+        numeric: bool,
+    ) -> Vec<(String, ValidationErrorKind)> {
+        let pattern = || validator.format.clone().unwrap_or_default();
+        let kinds = match &validator.compiled_format {
+            Some(CompiledFormat::Constraints(constraints)) if numeric => {
+                constraints.check_number(text, value)
+            }
+            Some(CompiledFormat::Constraints(constraints)) => constraints.check_text(text, value),
+            Some(CompiledFormat::Regex(re)) if re.is_match(text) => vec![],
+            Some(CompiledFormat::Regex(_)) => vec![ValidationErrorKind::FormatMismatch {
+                pattern: pattern(),
+                value: value.clone(),
+            }],
+            Some(CompiledFormat::Invalid) => {
+                vec![ValidationErrorKind::InvalidFormat { pattern: pattern() }]
+            }
+            Some(CompiledFormat::DateTime(_)) | None => vec![],
+        };
+        kinds
+            .into_iter()
+            .map(|kind| (path.to_string(), kind))
+            .collect()
+    }
+
+    /// Parses cardinality (`min-max`, `min-`, `-max` or exact count) into
+    /// minimal and optional maximal number of elements.
+    fn parse_cardinality(cardinality: &str) -> Option<(usize, Option<usize>)> {
+        let (min, max) = match cardinality.trim().split_once('-') {
+            Some((min, max)) => {
+                let min = match min.trim() {
+                    "" => 0,
+                    min => min.parse().ok()?,
+                };
+                let max = match max.trim() {
+                    "" => None,
+                    max => Some(max.parse().ok()?),
+                };
+                (min, max)
+            }
+            None => {
+                let count = cardinality.trim().parse().ok()?;
+                (count, Some(count))
+            }
+        };
+        match max {
+            Some(max) if max < min => None,
+            _ => Some((min, max)),
+        }
+    }
+
+    fn code_list(&self, said: &str) -> Option<Vec<String>> {
+        match self.code_lists.get(said) {
+            Some(codes) => Some(codes.clone()),
+            None => self
+                .code_list_provider
+                .as_ref()
+                .and_then(|provider| provider.code_list(said)),
+        }
+    }
+
+    /// Checks whether value is valid content in given encoding. Base64 accepts
+    /// both standard and URL-safe alphabets, with or without padding.
+    fn is_encoded(value: &str, encoding: Encoding) -> bool {
+        match encoding {
+            Encoding::Utf8 => true,
+            Encoding::Iso8859_1 => value.chars().all(|c| u32::from(c) <= 0xFF),
+            Encoding::Base64 => [
+                base64::STANDARD,
+                base64::STANDARD_NO_PAD,
+                base64::URL_SAFE,
+                base64::URL_SAFE_NO_PAD,
+            ]
+            .iter()
+            .any(|config| base64::decode_config(value, *config).is_ok()),
+        }
+    }
+
+    /// Splits attribute type into its type and SAID of referenced capture
+    /// base, i.e. `Array[Reference:SAID]` into `Array[Reference]` and `SAID`.
+    fn parse_attribute_type(attr_type: &str) -> (AttributeType, Option<String>) {
+        let (type_name, reference) = match attr_type.split_once(':') {
+            Some((type_name, said)) => {
+                let said = said.trim_end_matches(']').trim();
+                (
+                    if type_name.starts_with("Array[") {
+                        "Array[Reference]"
+                    } else {
+                        type_name
+                    },
+                    Some(said.to_string()).filter(|said| !said.is_empty()),
+                )
+            }
+            None => (attr_type, None),
+        };
+        (
+            serde_json::from_str::<AttributeType>(format!("\"{}\"", type_name).as_str()).unwrap(),
+            reference,
+        )
+    }
+
     fn parse_oca_attributes_to_validators(oca: &OCA) -> HashMap<String, AttributeValidator> {
         let mut attribute_validators: HashMap<String, AttributeValidator> = HashMap::new();
         for (attr_name, attr_type) in &oca.capture_base.attributes {
-            let mut validator = AttributeValidator::new(
-                attr_name.to_string(),
-                serde_json::from_str::<AttributeType>(format!("\"{}\"", attr_type).as_str())
-                    .unwrap(),
-            );
+            let (attribute_type, reference) = Self::parse_attribute_type(attr_type);
+            let mut validator = AttributeValidator::new(attr_name.to_string(), attribute_type);
+            validator.reference = reference;
 
             for overlay in &oca.overlays {
                 if overlay.attributes().contains(&attr_name) {
@@ -338,10 +996,29 @@ impl Validator {
                             .unwrap();
                         validator.conformance =
                             Some(ov.attribute_conformance.get(attr_name).unwrap().to_string())
+                    } else if overlay.overlay_type().contains("/conditional/") {
+                        let ov = overlay
+                            .as_any()
+                            .downcast_ref::<overlay::Conditional>()
+                            .unwrap();
+                        validator.condition = Some(Condition::new(
+                            ov.attribute_conditions.get(attr_name).unwrap(),
+                            ov.attribute_dependencies
+                                .get(attr_name)
+                                .cloned()
+                                .unwrap_or_default(),
+                        ))
+                    } else if overlay.overlay_type().contains("/cardinality/") {
+                        let ov = overlay
+                            .as_any()
+                            .downcast_ref::<overlay::Cardinality>()
+                            .unwrap();
+                        validator.cardinality =
+                            Some(ov.attribute_cardinality.get(attr_name).unwrap().to_string())
                     } else if overlay.overlay_type().contains("/format/") {
                         let ov = overlay.as_any().downcast_ref::<overlay::Format>().unwrap();
-                        validator.format =
-                            Some(ov.attribute_formats.get(attr_name).unwrap().to_string())
+                        validator
+                            .set_format(ov.attribute_formats.get(attr_name).unwrap().to_string())
                     } else if overlay.overlay_type().contains("/entry_code/") {
                         let ov = overlay
                             .as_any()
@@ -392,7 +1069,7 @@ mod tests {
             .to_string(),
         ));
         let validation_result = validator.validate();
-        assert!(validation_result.is_ok());
+        assert!(validation_result.success);
     }
 
     #[test]
@@ -405,7 +1082,88 @@ test@example.com;["A"];1;[22, "23"];01.01.1999;["01.01.2000"];true;[false, true]
                 .to_string(),
         ));
         let validation_result = validator.validate();
-        assert!(validation_result.is_ok());
+        assert!(validation_result.success);
+    }
+
+    #[test]
+    fn validation_of_single_data_set_should_not_accumulate_records() {
+        let oca = setup_oca();
+        let validator = Validator::new(oca);
+        let invalid = JSONDataSet::new(r#"{ "email*": "test@example.com" }"#.to_string());
+        let valid =
+            JSONDataSet::new(r#"{ "email*": "a@example.com", "licenses*": ["A"] }"#.to_string());
+
+        let errors = validator.validate_data_set(invalid.as_ref()).errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].attribute_name, "licenses*");
+        assert!(validator.validate_data_set(valid.as_ref()).success);
+        assert!(validator.data_sets.is_empty());
+    }
+
+    #[test]
+    fn validation_of_single_record_should_return_its_errors() {
+        let oca = setup_oca();
+        let validator = Validator::new(oca);
+
+        assert!(
+            validator
+                .validate_record(
+                    &serde_json::json!({ "email*": "a@example.com", "licenses*": ["A"] })
+                )
+                .success
+        );
+        let errors = validator
+            .validate_record(&serde_json::json!({ "email*": "a@example.com", "number": "x" }))
+            .errors;
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.data_set == "0" && e.record == "0"));
+        assert_eq!(
+            validator.validate_record(&Value::from("a")).errors[0].kind,
+            ValidationErrorKind::ParseError {
+                message: "record must be an object".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn validation_of_malformed_json_data_set_should_return_parse_errors() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"[{ "email*": "a@example.com", "licenses*": ["A"] }"#.to_string(),
+        ));
+        validator.add_data_set(JSONDataSet::new(
+            r#"[{ "email*": "a@example.com", "licenses*": ["A"] }, "a", 1]"#.to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            (errors[0].data_set.as_str(), errors[0].record.as_str()),
+            ("0", "")
+        );
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::ParseError {
+                message:
+                    "Data set is not valid JSON (EOF while parsing a list at line 1 column 50)"
+                        .to_string()
+            }
+        );
+        assert_eq!(
+            (errors[1].data_set.as_str(), errors[1].record.as_str()),
+            ("1", "1")
+        );
+        assert_eq!(
+            (errors[2].data_set.as_str(), errors[2].record.as_str()),
+            ("1", "2")
+        );
+        assert_eq!(
+            errors[2].kind,
+            ValidationErrorKind::ParseError {
+                message: "record must be an object".to_string()
+            }
+        );
     }
 
     #[test]
@@ -418,7 +1176,7 @@ test@example.com"#
                 .to_string(),
         ));
         let validation_result = validator.validate();
-        assert!(validation_result.is_err());
+        assert!(!validation_result.success);
     }
 
     #[test]
@@ -427,6 +1185,7 @@ test@example.com"#
         let mut validator = Validator::new(oca);
         validator.set_constraints(ConstraintsConfig {
             fail_on_additional_attributes: true,
+            ..Default::default()
         });
         validator.add_data_set(CSVDataSet::new(
             r#"email*;licenses*;additional
@@ -435,7 +1194,131 @@ test@example.com;["A"];1"#
         ));
         let validation_result = validator.validate();
 
-        assert!(validation_result.is_err());
+        assert!(!validation_result.success);
+    }
+
+    #[test]
+    fn validation_should_report_empty_optional_values_when_asked() {
+        let mut validator = Validator::new(setup_oca());
+        let record = serde_json::json!({
+            "email*": "test@example.com",
+            "licenses*": ["A"],
+            "number": " "
+        });
+
+        assert!(validator.validate_record(&record).errors.is_empty());
+        validator.set_constraints(ConstraintsConfig {
+            report_empty_values: true,
+            ..Default::default()
+        });
+        let errors = validator.validate_record(&record).errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ValidationErrorKind::EmptyValue);
+        assert_eq!(errors[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn validation_should_report_non_blocking_findings_as_warnings() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        validator.add_deprecated_entry_codes("licenses*", vec!["B".to_string()]);
+        validator.set_constraints(ConstraintsConfig {
+            report_empty_values: true,
+            ..Default::default()
+        });
+        let result = validator.validate_record(&serde_json::json!({
+            "email*": "test@example.com",
+            "licenses*": ["A", "B"],
+            "number": "",
+            "additional": 1
+        }));
+
+        assert!(result.success);
+        assert_eq!(
+            (result.error_count, result.warning_count, result.info_count),
+            (0, 3, 0)
+        );
+        assert_eq!(
+            result
+                .errors
+                .iter()
+                .map(|e| (e.attribute_name.as_str(), &e.kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "licenses*",
+                    &ValidationErrorKind::DeprecatedEntryCode {
+                        value: Value::from("B")
+                    }
+                ),
+                ("number", &ValidationErrorKind::EmptyValue),
+                ("additional", &ValidationErrorKind::UnknownAttribute),
+            ]
+        );
+
+        validator.set_constraints(ConstraintsConfig {
+            fail_on_additional_attributes: true,
+            ..Default::default()
+        });
+        let result = validator.validate_record(&serde_json::json!({
+            "email*": "test@example.com",
+            "licenses*": ["A"],
+            "additional": 1
+        }));
+        assert!(!result.success);
+        assert_eq!(result.errors[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn validation_should_follow_constraints_policy() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        let record = serde_json::json!({
+            "email*": " ",
+            "licenses*": ["A"],
+            "number": "23",
+            "numbers": [22, "23"]
+        });
+        let kinds = |result: ValidationResult| {
+            result
+                .errors
+                .into_iter()
+                .map(|e| (e.attribute_name, e.kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds(validator.validate_record(&record)),
+            vec![("email*".to_string(), ValidationErrorKind::MissingValue)]
+        );
+
+        validator.set_constraints(ConstraintsConfig {
+            strict_types: true,
+            whitespace_as_missing: false,
+            ..Default::default()
+        });
+        let errors = kinds(validator.validate_record(&record));
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].0, "email*");
+        assert!(matches!(
+            errors[0].1,
+            ValidationErrorKind::FormatMismatch { .. }
+        ));
+        assert_eq!(errors[1].0, "number");
+        assert!(matches!(
+            errors[1].1,
+            ValidationErrorKind::TypeMismatch { .. }
+        ));
+        assert_eq!(errors[2].0, "numbers");
+
+        validator.set_constraints(ConstraintsConfig {
+            strict_types: true,
+            max_errors: Some(1),
+            ..Default::default()
+        });
+        let result = validator.validate_record(&record);
+        assert_eq!(result.error_count, 1);
+        assert_eq!(result.errors.len(), 1);
     }
 
     #[test]
@@ -448,6 +1331,947 @@ test@example.com;["A"];1"#
             .to_string(),
         ));
         let validation_result = validator.validate();
-        assert!(validation_result.is_err());
+        assert!(!validation_result.success);
+    }
+
+    #[test]
+    fn validation_errors_should_have_structured_kind() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"{
+"licenses*": ["F"],
+"number": "a",
+"numbers": [1, "b"]
+      }"#
+            .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        let kinds = errors
+            .iter()
+            .map(|e| (e.attribute_name.as_str(), &e.kind))
+            .collect::<Vec<_>>();
+        assert!(kinds.contains(&(
+            "licenses*",
+            &ValidationErrorKind::EntryCodeNotAllowed {
+                allowed_codes: vec![
+                    "A".to_string(),
+                    "B".to_string(),
+                    "C".to_string(),
+                    "D".to_string(),
+                    "E".to_string()
+                ],
+                value: Value::from("F"),
+            }
+        )));
+        assert!(kinds.contains(&(
+            "number",
+            &ValidationErrorKind::TypeMismatch {
+                expected_type: "Numeric".to_string(),
+                value: Value::from("a"),
+            }
+        )));
+        assert!(kinds.contains(&("email*", &ValidationErrorKind::MissingAttribute)));
+        assert!(errors
+            .iter()
+            .any(|e| e.message.starts_with("'licenses*[0]' value (\"F\")")));
+
+        let element_error = errors
+            .iter()
+            .find(|e| e.attribute_name.eq("numbers"))
+            .unwrap();
+        assert_eq!(
+            element_error.message,
+            r#"'numbers[1]' value ("b") must be a Numeric type"#
+        );
+    }
+
+    #[test]
+    fn validation_errors_should_be_localised_when_language_is_set() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        validator.set_language("pl");
+        validator.add_data_set(JSONDataSet::new(
+            r#"{
+"email*": "test@example.com",
+"licenses*": ["A"],
+"number": "a"
+      }"#
+            .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            r#"Pole number ma nieprawidłową wartość "a", oczekiwany typ: Numeric"#
+        );
+    }
+
+    #[test]
+    fn validation_of_date_time_should_follow_format_overlay() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"[{
+"email*": "test@example.com",
+"licenses*": ["A"],
+"date": "31.02.1999",
+"dates": ["01.01.2000", "yesterday"]
+      }]"#
+            .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        let kinds = errors
+            .iter()
+            .map(|e| (e.attribute_name.as_str(), &e.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (
+                    "date",
+                    &ValidationErrorKind::InvalidDateTime {
+                        pattern: "DD.MM.YYYY".to_string(),
+                        value: Value::from("31.02.1999"),
+                    }
+                ),
+                (
+                    "dates",
+                    &ValidationErrorKind::InvalidDateTime {
+                        pattern: "DD.MM.YYYY".to_string(),
+                        value: Value::from("yesterday"),
+                    }
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn validation_of_values_should_follow_character_encoding_overlay() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": { "b64": "Binary", "latin": "Text", "b64s": "Array[Binary]" },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_character_encoding": {
+        "b64": "base64",
+        "b64s": "base64",
+        "latin": "iso-8859-1"
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "default_character_encoding": "utf-8",
+      "digest": "E4L-BukSBsqZoDDIJvw4_gGjAJs5It4UUfiA200lGup0",
+      "type": "spec/overlays/character_encoding/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"{ "b64": "aGVsbG8=", "b64s": ["aGVsbG8", "-_8"], "latin": "Zażółć" }"#.to_string(),
+        ));
+        validator.add_data_set(JSONDataSet::new(
+            r#"{ "b64": "not base64!", "latin": "Café" }"#.to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].data_set, "0");
+        assert_eq!(errors[0].attribute_name, "latin");
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::InvalidEncoding {
+                encoding: "iso-8859-1".to_string(),
+                value: Value::from("Zażółć"),
+            }
+        );
+        assert_eq!(errors[1].data_set, "1");
+        assert_eq!(errors[1].attribute_name, "b64");
+    }
+
+    #[test]
+    fn validation_of_reference_should_follow_referenced_oca() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, reference) in bundle.references {
+            validator.add_reference(&said, reference);
+        }
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        validator.add_data_set(JSONDataSet::new(
+            r#"[{
+"code": "glucose",
+"subject": { "name": "John", "birth_date": "31.02.1990" },
+"performers": [{ "name": "Anna" }, { "birth_date": "01.01.1980" }, "Tom"]
+      }, {
+"code": "glucose",
+"subject": { "name": "John" }
+      }]"#
+            .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        let errors = errors
+            .iter()
+            .map(|e| (e.record.as_str(), e.attribute_name.as_str(), &e.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.contains(&(
+            "0",
+            "subject.birth_date",
+            &ValidationErrorKind::InvalidDateTime {
+                pattern: "DD.MM.YYYY".to_string(),
+                value: Value::from("31.02.1990"),
+            }
+        )));
+        assert!(errors.contains(&(
+            "0",
+            "performers.name",
+            &ValidationErrorKind::MissingAttribute
+        )));
+        assert!(errors.contains(&(
+            "0",
+            "performers",
+            &ValidationErrorKind::TypeMismatch {
+                expected_type: "Reference".to_string(),
+                value: Value::from("Tom"),
+            }
+        )));
+    }
+
+    #[test]
+    fn validation_errors_of_references_should_be_labeled_from_referenced_oca() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, reference) in bundle.references {
+            validator.add_reference(&said, reference);
+        }
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        validator.set_language("en");
+        let errors = validator
+            .validate_record(&serde_json::json!({
+                "code": "glucose",
+                "subject": { "name": 1 },
+                "performers": [{ "name": "Anna" }, {}]
+            }))
+            .errors;
+
+        let errors = errors
+            .iter()
+            .map(|e| (e.attribute_name.as_str(), e.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.contains(&(
+            "subject.name",
+            "Subject.Name has invalid value 1, expected Text"
+        )));
+        assert!(errors.contains(&("performers.name", "Performers[1].Name is required")));
+    }
+
+    #[test]
+    fn validation_of_reference_without_referenced_oca_should_fail() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let said = bundle.references.keys().next().unwrap().clone();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        let result = validator.validate_record(&serde_json::json!({
+            "code": "glucose",
+            "subject": { "name": "John", "birth_date": "31.02.1990" }
+        }));
+
+        assert!(!result.success);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].attribute_name, "subject");
+        assert_eq!(
+            result.errors[0].kind,
+            ValidationErrorKind::UnresolvedReference { said }
+        );
+    }
+
+    #[test]
+    fn validation_should_report_deprecated_entry_codes_by_attribute_path() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, reference) in bundle.references {
+            validator.add_reference(&said, reference);
+        }
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        let record = serde_json::json!({
+            "code": "glucose",
+            "subject": { "name": "John", "gender": "female" },
+            "performers": [{ "name": "Anna", "gender": "female" }]
+        });
+        validator.set_code_list_provider(Box::new(GenderCodeListProvider));
+
+        validator.add_deprecated_entry_codes("gender", vec!["female".to_string()]);
+        assert!(validator.validate_record(&record).errors.is_empty());
+
+        validator.add_deprecated_entry_codes("subject.gender", vec!["female".to_string()]);
+        let errors = validator.validate_record(&record).errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].attribute_name, "subject.gender");
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::DeprecatedEntryCode {
+                value: Value::from("female")
+            }
+        );
+
+        validator.add_deprecated_entry_codes("performers.gender", vec!["female".to_string()]);
+        assert_eq!(validator.validate_record(&record).errors.len(), 2);
+    }
+
+    struct GenderCodeListProvider;
+
+    impl CodeListProvider for GenderCodeListProvider {
+        fn code_list(&self, _said: &str) -> Option<Vec<String>> {
+            Some(vec!["female".to_string(), "male".to_string()])
+        }
+    }
+
+    #[test]
+    fn validation_of_entry_codes_should_use_sai_code_lists() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let bundle = oca_zip_resolver::resolve_bundle_from_zip(
+            format!("{}/oca_bundle_with_references.zip", common_assets_dir_path).as_str(),
+        )
+        .unwrap();
+        let mut validator = Validator::new(bundle.oca);
+        for (said, reference) in bundle.references {
+            validator.add_reference(&said, reference);
+        }
+        for (said, codes) in bundle.code_lists {
+            validator.add_code_list(&said, codes);
+        }
+        let data_set = r#"[{
+"code": "glucose",
+"subject": { "name": "John", "gender": "female" }
+      }, {
+"code": "insulin",
+"subject": { "name": "John", "gender": "unknown" }
+      }]"#;
+        validator.add_data_set(JSONDataSet::new(data_set.to_string()));
+        let errors = validator.validate().errors;
+
+        let errors = errors
+            .iter()
+            .map(|e| (e.record.as_str(), e.attribute_name.as_str(), &e.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 3);
+        assert!(errors.contains(&(
+            "1",
+            "code",
+            &ValidationErrorKind::EntryCodeNotAllowed {
+                allowed_codes: vec!["glucose".to_string(), "cholesterol".to_string()],
+                value: Value::from("insulin"),
+            }
+        )));
+        assert!(errors
+            .iter()
+            .filter(|(_, attribute_name, kind)| {
+                attribute_name.eq(&"subject.gender")
+                    && matches!(kind, ValidationErrorKind::UnresolvedCodeList { .. })
+            })
+            .count()
+            .eq(&2));
+
+        validator.data_sets.clear();
+        validator.set_code_list_provider(Box::new(GenderCodeListProvider));
+        validator.add_data_set(JSONDataSet::new(data_set.to_string()));
+        let errors = validator.validate().errors;
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].attribute_name, "code");
+        assert_eq!(errors[1].attribute_name, "subject.gender");
+    }
+
+    #[test]
+    fn validation_of_arrays_should_follow_cardinality_overlay() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": {
+      "tags": "Array[Text]",
+      "scores": "Array[Numeric]",
+      "dates": "Array[DateTime]"
+    },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_cardinality": {
+        "tags": "1-3",
+        "scores": "2-",
+        "dates": "3-1"
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EeYP1yzvzB6wQA3Ln2vz0ajzpaM8Ihg9N8k0DEJrBaXM",
+      "type": "spec/overlays/cardinality/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"[
+{ "tags": ["a"], "scores": [1, 2, 3] },
+{ "tags": ["a", "b", "c", "d"], "scores": [1] }
+]"#
+            .to_string(),
+        ));
+        validator.add_data_set(CSVDataSet::new(
+            r#"tags;scores;dates
+[];[1, 2];["01.01.2000"]"#
+                .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        let errors = errors
+            .iter()
+            .map(|e| {
+                (
+                    e.data_set.as_str(),
+                    e.record.as_str(),
+                    e.attribute_name.as_str(),
+                    &e.kind,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "0",
+                    "1",
+                    "tags",
+                    &ValidationErrorKind::CardinalityMismatch {
+                        cardinality: "1-3".to_string(),
+                        count: 4,
+                    }
+                ),
+                (
+                    "0",
+                    "1",
+                    "scores",
+                    &ValidationErrorKind::CardinalityMismatch {
+                        cardinality: "2-".to_string(),
+                        count: 1,
+                    }
+                ),
+                (
+                    "1",
+                    "0",
+                    "tags",
+                    &ValidationErrorKind::CardinalityMismatch {
+                        cardinality: "1-3".to_string(),
+                        count: 0,
+                    }
+                ),
+                (
+                    "1",
+                    "0",
+                    "dates",
+                    &ValidationErrorKind::InvalidCardinality {
+                        cardinality: "3-1".to_string(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_cardinality_ranges() {
+        assert_eq!(Validator::parse_cardinality("1-3"), Some((1, Some(3))));
+        assert_eq!(Validator::parse_cardinality("2-"), Some((2, None)));
+        assert_eq!(Validator::parse_cardinality("-2"), Some((0, Some(2))));
+        assert_eq!(Validator::parse_cardinality("2"), Some((2, Some(2))));
+        assert_eq!(Validator::parse_cardinality("n"), None);
+    }
+
+    #[test]
+    fn validation_should_follow_conditional_overlay() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": {
+      "sex": "Text",
+      "pregnancy_weeks": "Numeric",
+      "smoker": "Boolean",
+      "cigarettes": "Numeric"
+    },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_conformance": {
+        "sex": "M",
+        "pregnancy_weeks": "M",
+        "cigarettes": "M"
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EVFzh9WFOzRaTRzaH2nUSfnaX1GmOHDfhEtlWmdbB6KY",
+      "type": "spec/overlays/conformance/1.0"
+    },
+    {
+      "attribute_conditions": {
+        "pregnancy_weeks": "${0} == 'F'",
+        "cigarettes": "${0} && ${1} =="
+      },
+      "attribute_dependencies": {
+        "pregnancy_weeks": ["sex"],
+        "cigarettes": ["smoker", "sex"]
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EkyIEvDwMete4Y-adBJZcGihh6K2Orswhkv_unFmXzHM",
+      "type": "spec/overlays/conditional/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"[
+{ "sex": "M", "pregnancy_weeks": "not relevant" },
+{ "sex": "F", "pregnancy_weeks": 12 },
+{ "sex": "F" },
+{ "sex": "F", "pregnancy_weeks": null }
+]"#
+            .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        let required_by_sex = ValidationErrorKind::RequiredByCondition {
+            condition: "sex == 'F'".to_string(),
+        };
+        let invalid_condition = ValidationErrorKind::InvalidCondition {
+            condition: "${0} && ${1} ==".to_string(),
+        };
+        let errors = errors
+            .iter()
+            .map(|e| (e.record.as_str(), e.attribute_name.as_str(), &e.kind))
+            .collect::<Vec<_>>();
+        let missing = ValidationErrorKind::MissingAttribute;
+        assert_eq!(
+            errors,
+            vec![
+                ("", "cigarettes", &invalid_condition),
+                (
+                    "0",
+                    "pregnancy_weeks",
+                    &ValidationErrorKind::TypeMismatch {
+                        expected_type: "Numeric".to_string(),
+                        value: Value::from("not relevant"),
+                    },
+                ),
+                ("0", "cigarettes", &missing),
+                ("1", "cigarettes", &missing),
+                ("2", "cigarettes", &missing),
+                ("2", "pregnancy_weeks", &required_by_sex),
+                ("3", "pregnancy_weeks", &required_by_sex),
+                ("3", "cigarettes", &missing),
+            ]
+        );
+        assert_eq!(
+            validator
+                .validate_record(&serde_json::json!({ "sex": "M", "cigarettes": 5 }))
+                .errors
+                .iter()
+                .map(|e| (e.record.as_str(), &e.kind))
+                .collect::<Vec<_>>(),
+            vec![("", &invalid_condition)]
+        );
+        assert_eq!(
+            validator
+                .validate_record(&serde_json::json!({
+                    "sex": "M",
+                    "pregnancy_weeks": null,
+                    "cigarettes": 5
+                }))
+                .errors
+                .len(),
+            1
+        );
+        assert_eq!(
+            ValidationErrorKind::RequiredByCondition {
+                condition: "sex == 'F'".to_string()
+            }
+            .message("pregnancy_weeks"),
+            "'pregnancy_weeks' is required when sex == 'F'"
+        );
+    }
+
+    #[test]
+    fn validation_of_numeric_values_should_follow_format_regex() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": { "score": "Numeric" },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_formats": { "score": "[0-9]{2}" },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EGiJKfiFCIf8Hdt8QLlnksOB9AH6_gcuc0l3gx0aATnM",
+      "type": "spec/overlays/format/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+        let validator = Validator::new(oca);
+
+        assert!(
+            validator
+                .validate_record(&serde_json::json!({ "score": 12 }))
+                .success
+        );
+        let errors = validator
+            .validate_record(&serde_json::json!({ "score": 12.5 }))
+            .errors;
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::FormatMismatch {
+                pattern: "[0-9]{2}".to_string(),
+                value: Value::from(12.5),
+            }
+        );
+    }
+
+    #[test]
+    fn validation_should_reject_constraints_of_other_attribute_type() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": { "code": "Text", "count": "Numeric" },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_formats": {
+        "code": "constraints:min=0;max=50;decimals=2",
+        "count": "constraints:min_length=1;max_length=2"
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EGiJKfiFCIf8Hdt8QLlnksOB9AH6_gcuc0l3gx0aATnM",
+      "type": "spec/overlays/format/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+        let validator = Validator::new(oca);
+
+        let mut errors = validator
+            .validate_record(&serde_json::json!({ "code": "ab", "count": 123 }))
+            .errors
+            .into_iter()
+            .map(|e| (e.attribute_name, e.kind))
+            .collect::<Vec<_>>();
+        errors.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "code".to_string(),
+                    ValidationErrorKind::InvalidFormat {
+                        pattern: "constraints:min=0;max=50;decimals=2".to_string()
+                    }
+                ),
+                (
+                    "count".to_string(),
+                    ValidationErrorKind::InvalidFormat {
+                        pattern: "constraints:min_length=1;max_length=2".to_string()
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validation_of_values_should_follow_format_constraints() {
+        let oca = oca_rs::controller::load_oca(
+            &mut r#"{
+  "capture_base": {
+    "attributes": { "glucose": "Numeric", "count": "Numeric", "code": "Text", "id": "Text" },
+    "classification": "",
+    "digest": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+    "flagged_attributes": [],
+    "type": "spec/capture_base/1.0"
+  },
+  "overlays": [
+    {
+      "attribute_formats": {
+        "glucose": "constraints:min=0;max=50;decimals=2",
+        "count": "constraints:integer;min=1",
+        "code": "constraints:min_length=2;max_length=4",
+        "id": "[0-9]+"
+      },
+      "capture_base": "ElNWOR0fQbv_J6EL0pJlvCxEpbu4bg1AurHgr_0A7LKc",
+      "digest": "EGiJKfiFCIf8Hdt8QLlnksOB9AH6_gcuc0l3gx0aATnM",
+      "type": "spec/overlays/format/1.0"
+    }
+  ]
+}"#
+            .as_bytes(),
+        )
+        .unwrap()
+        .finalize();
+
+        let mut validator = Validator::new(oca);
+        validator.add_data_set(JSONDataSet::new(
+            r#"[
+{ "glucose": 5.25, "count": 3, "code": "ab", "id": "12" },
+{ "glucose": 50.5, "count": "1.5", "code": "abcde", "id": "a1" },
+{ "glucose": "5.125", "count": 0 }
+]"#
+            .to_string(),
+        ));
+        let mut errors = validator
+            .validate()
+            .errors
+            .into_iter()
+            .map(|e| (e.record, e.attribute_name, e.kind))
+            .collect::<Vec<_>>();
+        errors.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+
+        assert_eq!(
+            errors,
+            vec![
+                (
+                    "1".to_string(),
+                    "code".to_string(),
+                    ValidationErrorKind::LengthOutOfRange {
+                        range: "[2, 4]".to_string(),
+                        value: Value::from("abcde"),
+                    }
+                ),
+                (
+                    "1".to_string(),
+                    "count".to_string(),
+                    ValidationErrorKind::TooManyDecimals {
+                        decimals: 0,
+                        value: Value::from("1.5"),
+                    }
+                ),
+                (
+                    "1".to_string(),
+                    "glucose".to_string(),
+                    ValidationErrorKind::OutOfRange {
+                        range: "[0, 50]".to_string(),
+                        value: Value::from(50.5),
+                    }
+                ),
+                (
+                    "1".to_string(),
+                    "id".to_string(),
+                    ValidationErrorKind::FormatMismatch {
+                        pattern: "[0-9]+".to_string(),
+                        value: Value::from("a1"),
+                    }
+                ),
+                (
+                    "2".to_string(),
+                    "count".to_string(),
+                    ValidationErrorKind::OutOfRange {
+                        range: "[1, ∞)".to_string(),
+                        value: Value::from(0),
+                    }
+                ),
+                (
+                    "2".to_string(),
+                    "glucose".to_string(),
+                    ValidationErrorKind::TooManyDecimals {
+                        decimals: 2,
+                        value: Value::from("5.125"),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn validation_of_stream_should_report_errors_incrementally() {
+        let oca = setup_oca();
+        let validator = Validator::new(oca);
+        let mut stream = crate::data_set::CSVDataStream::new(
+            r#"email*;licenses*;number
+a@example.com;["A"];1
+b@example.com;["A"];x
+c@example.com;;2
+d@example.com;;3"#
+                .as_bytes(),
+        );
+
+        let mut errors = vec![];
+        let count = validator.validate_stream(&mut stream, Some(2), |e| errors.push(e));
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.record.as_str(), e.attribute_name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("1", "number"), ("2", "licenses*")]
+        );
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::ParseError { .. }
+        ));
+
+        let mut stream = crate::data_set::NDJSONDataStream::new(
+            "{\"email*\": \"a@example.com\", \"licenses*\": [\"A\"]}\nnot json\n".as_bytes(),
+        );
+        let mut errors = vec![];
+        assert_eq!(
+            validator.validate_stream(&mut stream, None, |e| errors.push(e)),
+            1
+        );
+        assert_eq!(errors[0].record, "1");
+        assert_eq!(errors[0].attribute_name, "");
+    }
+
+    #[test]
+    fn validation_should_continue_after_invalid_ndjson_lines() {
+        let mut validator = Validator::new(setup_oca());
+        validator.add_data_set(crate::data_set::NDJSONDataSet::new(
+            "{\"email*\": \"a@example.com\", \"licenses*\": [\"A\"]}\nnot json\n{\"email*\": \"b@example.com\"}"
+                .to_string(),
+        ));
+        let errors = validator.validate().errors;
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.record.as_str(), e.attribute_name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![("1", ""), ("2", "licenses*")]
+        );
+        assert!(matches!(
+            errors[0].kind,
+            ValidationErrorKind::ParseError { .. }
+        ));
+        assert_eq!(errors[1].kind, ValidationErrorKind::MissingAttribute);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_validation_should_keep_sequential_order() {
+        let validator = Validator::new(setup_oca());
+        let records = (0..700)
+            .map(|i| match i % 5 {
+                0 => serde_json::json!({ "email*": "a@example.com" }),
+                1 => serde_json::json!({ "email*": "a@example.com", "licenses*": ["A"], "number": "x" }),
+                2 => serde_json::json!({ "additional": i, "number": i }),
+                _ => serde_json::json!({ "email*": "a@example.com", "licenses*": ["A"] }),
+            })
+            .collect::<Vec<_>>();
+        assert!(records.len() > 2 * PARALLEL_CHUNK_SIZE);
+        let findings = |errors: Vec<ValidationError>| {
+            errors
+                .into_iter()
+                .map(|e| (e.record, e.attribute_name, e.kind))
+                .collect::<Vec<_>>()
+        };
+
+        let sequential = records
+            .iter()
+            .enumerate()
+            .flat_map(|(record_index, record)| validator.record_errors(0, record_index, record))
+            .collect();
+        let parallel = validator
+            .validate_data_set(JSONDataSet::new(serde_json::to_string(&records).unwrap()).as_ref())
+            .errors;
+
+        assert_eq!(findings(parallel), findings(sequential));
+    }
+
+    #[test]
+    fn validation_errors_should_be_ordered_by_record_index() {
+        let oca = setup_oca();
+        let mut validator = Validator::new(oca);
+        let records = (0..1000)
+            .map(|i| match i % 3 {
+                0 => serde_json::json!({ "email*": "a@example.com", "licenses*": ["A"] }),
+                _ => serde_json::json!({ "email*": "a@example.com", "number": i }),
+            })
+            .collect::<Vec<_>>();
+        validator.add_data_set(JSONDataSet::new(serde_json::to_string(&records).unwrap()));
+        validator.add_data_set(JSONDataSet::new(r#"{ "number": "x" }"#.to_string()));
+        let errors = validator.validate().errors;
+
+        let expected = (0..1000)
+            .filter(|i| i % 3 != 0)
+            .map(|i| ("0".to_string(), i.to_string()))
+            .chain(["1", "1", "1"].map(|d| (d.to_string(), "0".to_string())))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors
+                .iter()
+                .map(|e| (e.data_set.clone(), e.record.clone()))
+                .collect::<Vec<_>>(),
+            expected
+        );
     }
 }
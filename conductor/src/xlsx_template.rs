@@ -0,0 +1,293 @@
+use crate::errors::GenericError;
+use oca_rs::state::{
+    attribute::AttributeType,
+    entry_codes::EntryCodes,
+    oca::{overlay, DynOverlay, OCA},
+};
+use rust_xlsxwriter::{DataValidation, Format, Formula, Note, Workbook, Worksheet};
+use std::collections::BTreeMap;
+
+pub const DATA_SHEET: &str = "Data";
+pub const METADATA_SHEET: &str = "Metadata";
+pub const ENTRY_CODES_SHEET: &str = "Entry codes";
+
+/// Number of rows in data sheet covered by data validation.
+const TEMPLATE_ROWS: u32 = 1000;
+
+#[derive(Default)]
+struct AttributeMetadata {
+    label: Option<String>,
+    information: Option<String>,
+    conformance: Option<String>,
+    format: Option<String>,
+    unit: Option<String>,
+    entry_codes: Option<Vec<String>>,
+}
+
+/// Generates XLSX workbook to be filled in with data captured by given OCA.
+///
+/// `Data` sheet has a column per capture base attribute, labelled from Label
+/// overlay in `language` (attribute name is used when label is missing).
+/// Information overlay texts are attached to headers as notes and entry codes
+/// restrict column values with a dropdown. Hidden `Metadata` sheet describes
+/// attributes. Filled in workbook can be loaded back with `XLSXDataSet` using
+/// `XLSXDataSet::label_header_mapping`.
+pub fn generate_xlsx_template(oca: &OCA, language: &str) -> Result<Vec<u8>, GenericError> {
+    let attributes = collect_metadata(oca, language);
+    let header_format = Format::new().set_bold();
+
+    let mut workbook = Workbook::new();
+
+    let data_sheet = workbook.add_worksheet();
+    data_sheet.set_name(DATA_SHEET)?;
+    data_sheet.set_freeze_panes(1, 0)?;
+    for (col, (attr_name, metadata)) in attributes.iter().enumerate() {
+        let col = u16::try_from(col)?;
+        let header = metadata.label.as_ref().unwrap_or(attr_name);
+        data_sheet.write_string_with_format(0, col, header, &header_format)?;
+        data_sheet.set_column_width(col, header.chars().count().max(12) as f64 + 2.0)?;
+        if let Some(information) = &metadata.information {
+            data_sheet.insert_note(0, col, &Note::new(information).add_author_prefix(false))?;
+        }
+    }
+
+    let mut entry_code_columns: Vec<(u16, u16, usize)> = vec![];
+    let entry_codes_sheet = workbook.add_worksheet();
+    entry_codes_sheet.set_name(ENTRY_CODES_SHEET)?;
+    entry_codes_sheet.set_hidden(true);
+    let mut entry_codes_col = 0;
+    for (col, (attr_name, metadata)) in attributes.iter().enumerate() {
+        if let Some(codes) = &metadata.entry_codes {
+            let attribute_type = oca
+                .capture_base
+                .attributes
+                .get(attr_name)
+                .and_then(|t| serde_json::from_str::<AttributeType>(&format!("\"{t}\"")).ok());
+            entry_codes_sheet.write_string(0, entry_codes_col, attr_name)?;
+            for (row, code) in codes.iter().enumerate() {
+                entry_codes_sheet.write_string(u32::try_from(row)? + 1, entry_codes_col, code)?;
+            }
+            // Dropdown allows a single value only, so arrays are left unrestricted.
+            if !codes.is_empty() && !is_array(attribute_type) {
+                entry_code_columns.push((u16::try_from(col)?, entry_codes_col, codes.len()));
+            }
+            entry_codes_col += 1;
+        }
+    }
+
+    let metadata_sheet = workbook.add_worksheet();
+    metadata_sheet.set_name(METADATA_SHEET)?;
+    metadata_sheet.set_hidden(true);
+    write_metadata(metadata_sheet, oca, language, &attributes, &header_format)?;
+
+    let data_sheet = workbook.worksheet_from_name(DATA_SHEET)?;
+    for (col, entry_codes_col, codes_count) in entry_code_columns {
+        let column_name = column_name(entry_codes_col);
+        let validation = DataValidation::new().allow_list_formula(Formula::new(format!(
+            "='{ENTRY_CODES_SHEET}'!${column_name}$2:${column_name}${}",
+            codes_count + 1
+        )));
+        data_sheet.add_data_validation(1, col, TEMPLATE_ROWS, col, &validation)?;
+    }
+    data_sheet.set_active(true);
+
+    Ok(workbook.save_to_buffer()?)
+}
+
+fn write_metadata(
+    sheet: &mut Worksheet,
+    oca: &OCA,
+    language: &str,
+    attributes: &BTreeMap<String, AttributeMetadata>,
+    header_format: &Format,
+) -> Result<(), GenericError> {
+    sheet.write_string_with_format(0, 0, "Capture base", header_format)?;
+    sheet.write_string(0, 1, &oca.capture_base.said)?;
+    sheet.write_string_with_format(1, 0, "Language", header_format)?;
+    sheet.write_string(1, 1, language)?;
+
+    let headers = [
+        "Attribute",
+        "Label",
+        "Type",
+        "Conformance",
+        "Format",
+        "Unit",
+        "Entry codes",
+        "Information",
+    ];
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(3, u16::try_from(col)?, *header, header_format)?;
+    }
+    for (i, (attr_name, metadata)) in attributes.iter().enumerate() {
+        let row = u32::try_from(i)? + 4;
+        let values = [
+            Some(attr_name.clone()),
+            metadata.label.clone(),
+            oca.capture_base.attributes.get(attr_name).cloned(),
+            metadata.conformance.clone(),
+            metadata.format.clone(),
+            metadata.unit.clone(),
+            metadata.entry_codes.as_ref().map(|codes| codes.join(", ")),
+            metadata.information.clone(),
+        ];
+        for (col, value) in values.iter().enumerate() {
+            if let Some(value) = value {
+                sheet.write_string(row, u16::try_from(col)?, value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn collect_metadata(oca: &OCA, language: &str) -> BTreeMap<String, AttributeMetadata> {
+    let mut attributes: BTreeMap<String, AttributeMetadata> = oca
+        .capture_base
+        .attributes
+        .keys()
+        .map(|attr_name| (attr_name.clone(), AttributeMetadata::default()))
+        .collect();
+
+    for overlay in &oca.overlays {
+        if !is_in_language(overlay, language) {
+            continue;
+        }
+        let overlay_type = overlay.overlay_type();
+        for (attr_name, metadata) in attributes.iter_mut() {
+            if overlay_type.contains("/label/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Label>() {
+                    if let Some(label) = ov.attribute_labels.get(attr_name) {
+                        metadata.label = Some(label.clone());
+                    }
+                }
+            } else if overlay_type.contains("/information/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Information>() {
+                    if let Some(information) = ov.attribute_information.get(attr_name) {
+                        metadata.information = Some(information.clone());
+                    }
+                }
+            } else if overlay_type.contains("/conformance/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Conformance>() {
+                    if let Some(conformance) = ov.attribute_conformance.get(attr_name) {
+                        metadata.conformance = Some(conformance.clone());
+                    }
+                }
+            } else if overlay_type.contains("/format/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Format>() {
+                    if let Some(format) = ov.attribute_formats.get(attr_name) {
+                        metadata.format = Some(format.clone());
+                    }
+                }
+            } else if overlay_type.contains("/unit/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::Unit>() {
+                    if let Some(unit) = ov.attribute_units.get(attr_name) {
+                        metadata.unit = Some(format!("{}:{}", ov.metric_system, unit));
+                    }
+                }
+            } else if overlay_type.contains("/entry_code/") {
+                if let Some(ov) = overlay.as_any().downcast_ref::<overlay::EntryCode>() {
+                    if let Some(EntryCodes::Array(codes)) = ov.attribute_entry_codes.get(attr_name)
+                    {
+                        metadata.entry_codes = Some(codes.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    attributes
+}
+
+fn is_in_language(overlay: &DynOverlay, language: &str) -> bool {
+    match overlay.language() {
+        Some(overlay_language) => overlay_language.eq(language),
+        None => true,
+    }
+}
+
+fn is_array(attribute_type: Option<AttributeType>) -> bool {
+    matches!(
+        attribute_type,
+        Some(
+            AttributeType::ArrayText
+                | AttributeType::ArrayNumeric
+                | AttributeType::ArrayBoolean
+                | AttributeType::ArrayDateTime
+                | AttributeType::ArrayBinary
+                | AttributeType::ArrayReference
+        )
+    )
+}
+
+fn column_name(col: u16) -> String {
+    let mut name = String::new();
+    let mut n = u32::from(col) + 1;
+    while n > 0 {
+        let rem = (n - 1) % 26;
+        name.insert(0, char::from(b'A' + rem as u8));
+        n = (n - 1) / 26;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_set::{DataSet, XLSXDataSet};
+    use calamine::{open_workbook_from_rs, Reader, Xlsx};
+    use std::io::Cursor;
+
+    fn setup_oca() -> OCA {
+        let examples_dir_path = format!("{}/../examples", env!("CARGO_MANIFEST_DIR"));
+        let oca_result = oca_zip_resolver::resolve_from_zip(
+            format!("{examples_dir_path}/glucose_records/oca_bundle.zip").as_str(),
+        );
+        assert!(oca_result.is_ok());
+        oca_result.unwrap()
+    }
+
+    #[test]
+    fn generate_template_with_labelled_columns() {
+        let oca = setup_oca();
+        let result = generate_xlsx_template(&oca, "en");
+        assert!(result.is_ok());
+        let template = result.unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook_from_rs(Cursor::new(template.clone())).unwrap();
+        assert_eq!(
+            workbook.sheet_names(),
+            &[DATA_SHEET, ENTRY_CODES_SHEET, METADATA_SHEET]
+        );
+        let data_range = workbook.worksheet_range(DATA_SHEET).unwrap().unwrap();
+        let headers = data_range
+            .rows()
+            .next()
+            .unwrap()
+            .iter()
+            .map(|cell| cell.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(headers.len(), oca.capture_base.attributes.len());
+
+        let mapping = XLSXDataSet::label_header_mapping(&oca, "en");
+        for header in &headers {
+            assert!(
+                mapping.contains_key(header) || oca.capture_base.attributes.contains_key(header)
+            );
+        }
+
+        let records = XLSXDataSet::from_bytes(template)
+            .header_mapping(mapping)
+            .load(oca.capture_base.attributes.clone());
+        assert!(records.is_ok());
+        assert!(records.unwrap().is_empty());
+    }
+
+    #[test]
+    fn convert_column_index_to_name() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+    }
+}
@@ -9,7 +9,7 @@ const transformer = new Transformer(oca)
 const unit_overlay = `
   {
     "attribute_units": {
-      "valueQuantity_14745-4": "mg/dL"
+      "valueQuantity_14745-4": "mg/dL:glucose"
     },
     "capture_base": "E9V_1S5JwM9HA5qut8xfKlZLKuZTCJlEzQFNJs4MbBuY",
     "digest": "E1-FcPs6Y_ct6UTVoE3Ok0juGLFmOic3uUywRUJBn-P8",
@@ -1,47 +1,107 @@
-use oca_rs::state::oca::OCA;
-use std::collections::HashMap;
+use oca_rs::state::{
+    entry_codes::EntryCodes,
+    oca::{overlay, OCA},
+};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io::BufReader;
+use std::io::Cursor;
 use std::io::Read;
+use std::io::Seek;
 
 #[derive(Debug)]
 struct ResolvedFile {
     meta: serde_json::Value,
     files: HashMap<String, String>,
+    /// Overlay SAIDs of each capture base listed in `files` of meta.json
+    /// files found in the bundle.
+    capture_bases: BTreeMap<String, Vec<String>>,
+}
+
+/// OCA Bundle with OCAs of capture bases referenced by its attributes
+/// (`Reference:SAID` and `Array[Reference:SAID]` types) and code lists
+/// referenced by Entry Code overlays, both keyed by SAID.
+pub struct OCABundle {
+    pub oca: OCA,
+    pub references: BTreeMap<String, OCA>,
+    pub code_lists: BTreeMap<String, Vec<String>>,
 }
 
 pub fn resolve_from_zip(path: &str) -> Result<OCA, String> {
+    Ok(resolve_bundle_from_zip(path)?.oca)
+}
+
+/// Resolves OCA Bundle together with referenced capture bases. Files of
+/// referenced capture bases may be placed in the root of the archive or in
+/// directories, each listed in `files` of root or directory's meta.json.
+pub fn resolve_bundle_from_zip(path: &str) -> Result<OCABundle, String> {
     let fname = std::path::Path::new(path);
     let file =
         fs::File::open(fname).map_err(|e| format!("Error while loading {path} file. {e}"))?;
-    let reader = BufReader::new(file);
+    resolve_bundle(BufReader::new(file), path)
+}
+
+/// Resolves OCA Bundle from zip archive held in memory, i.e. uploaded file.
+/// `name` refers to the archive in error messages.
+pub fn resolve_bundle_from_bytes(bytes: &[u8], name: &str) -> Result<OCABundle, String> {
+    resolve_bundle(Cursor::new(bytes), name)
+}
 
+fn resolve_bundle<R: Read + Seek>(reader: R, path: &str) -> Result<OCABundle, String> {
     let mut archive = zip::ZipArchive::new(reader).map_err(|err| err.to_string())?;
 
     let mut resolved_file = ResolvedFile {
         meta: serde_json::Value::Null,
         files: HashMap::new(),
+        capture_bases: BTreeMap::new(),
     };
+    let mut root_file_names = vec![];
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i).unwrap();
-        if file.enclosed_name().is_none() {
-            return Err(format!("Entry {} has a suspicious path", file.name()));
+        let enclosed_name = match file.enclosed_name() {
+            Some(name) => name.to_path_buf(),
+            None => return Err(format!("Entry {} has a suspicious path", file.name())),
         };
 
-        if (*file.name()).contains('/') {
+        if file.is_dir() || file.name().starts_with("__MACOSX/") {
             continue;
+        }
+        let file_name = match enclosed_name.file_name().and_then(|n| n.to_str()) {
+            Some(file_name) => file_name.to_string(),
+            None => continue,
+        };
+
+        let mut buffer = String::new();
+        file.read_to_string(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        if file_name.eq("meta.json") {
+            let meta: serde_json::Value =
+                serde_json::from_str(buffer.as_str()).map_err(|err| err.to_string())?;
+            if let Some(serde_json::Value::Object(files)) = meta.get("files") {
+                for (capture_base_said, overlays) in files {
+                    resolved_file.capture_bases.insert(
+                        capture_base_said.clone(),
+                        overlays
+                            .as_object()
+                            .map(|o| {
+                                o.values()
+                                    .filter_map(|v| v.as_str().map(|v| v.to_string()))
+                                    .collect()
+                            })
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+            if !file.name().contains('/') {
+                resolved_file.meta = meta;
+            }
         } else {
-            let mut buffer = String::new();
-
-            file.read_to_string(&mut buffer)
-                .map_err(|err| err.to_string())?;
-            if file.name().to_string().eq(&String::from("meta.json")) {
-                resolved_file.meta =
-                    serde_json::from_str(buffer.as_str()).map_err(|err| err.to_string())?;
-            } else {
-                resolved_file.files.insert(file.name().to_string(), buffer);
+            let said = file_name.trim_end_matches(".json").to_string();
+            if !file.name().contains('/') {
+                root_file_names.push(said.clone());
             }
+            resolved_file.files.insert(said, buffer);
         }
     }
 
@@ -52,37 +112,131 @@ pub fn resolve_from_zip(path: &str) -> Result<OCA, String> {
     }
 
     let mut oca_option: Option<OCA> = None;
+    let mut references = BTreeMap::new();
     if let serde_json::Value::String(root_sai) = resolved_file
         .meta
         .get("root")
         .ok_or("Missing 'root' attribute in meta.json file")
         .map_err(|e| e.to_string())?
     {
-        let root_filename = format!("{root_sai}.json");
-        let root_file_content = resolved_file.files.remove(&root_filename).ok_or(format!(
-            "Malformed OCA Bundle ({}). Missing {} file.",
-            path, &root_filename
-        ))?;
-        let data = format!(
-            r#"{{"capture_base": {}, "overlays": [{}] }}"#,
-            root_file_content,
-            resolved_file
-                .files
-                .values()
-                .cloned()
-                .collect::<Vec<String>>()
-                .join(",")
-        );
+        if !resolved_file.files.contains_key(root_sai) {
+            return Err(format!(
+                "Malformed OCA Bundle ({path}). Missing {root_sai}.json file."
+            ));
+        }
+        let root_overlays = match resolved_file.capture_bases.get(root_sai) {
+            Some(overlays) => overlays.clone(),
+            None => root_file_names
+                .into_iter()
+                .filter(|said| said.ne(root_sai) && is_overlay(&resolved_file.files[said]))
+                .collect(),
+        };
+        oca_option = Some(load_oca(&resolved_file, path, root_sai, &root_overlays)?);
+
+        for (capture_base_said, overlays) in &resolved_file.capture_bases {
+            if capture_base_said.ne(root_sai) {
+                references.insert(
+                    capture_base_said.clone(),
+                    load_oca(&resolved_file, path, capture_base_said, overlays)?,
+                );
+            }
+        }
+    }
 
-        let oca_builder = oca_rs::controller::load_oca(&mut data.as_bytes()).unwrap();
-        oca_option = Some(oca_builder.finalize());
+    let mut code_lists = BTreeMap::new();
+    for oca in oca_option.iter().chain(references.values()) {
+        for said in code_list_saids(oca) {
+            if let Some(content) = resolved_file.files.get(&said) {
+                code_lists.insert(said, parse_code_list(content)?);
+            }
+        }
     }
 
     oca_option
+        .map(|oca| OCABundle {
+            oca,
+            references,
+            code_lists,
+        })
         .ok_or("Error while loading OCA Bundle")
         .map_err(|e| e.to_string())
 }
 
+fn is_overlay(file_content: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(file_content)
+        .ok()
+        .and_then(|v| {
+            v.get("type")
+                .and_then(|t| t.as_str())
+                .map(|t| t.starts_with("spec/overlays/"))
+        })
+        .unwrap_or(false)
+}
+
+fn code_list_saids(oca: &OCA) -> Vec<String> {
+    let mut saids = vec![];
+    for overlay in &oca.overlays {
+        if overlay.overlay_type().contains("/entry_code/") {
+            if let Some(ov) = overlay.as_any().downcast_ref::<overlay::EntryCode>() {
+                for entry_codes in ov.attribute_entry_codes.values() {
+                    if let EntryCodes::Sai(said) = entry_codes {
+                        saids.push(said.clone());
+                    }
+                }
+            }
+        }
+    }
+    saids
+}
+
+/// Parses code list file being an array of codes, an object with `codes`
+/// array or an object keyed by codes.
+fn parse_code_list(file_content: &str) -> Result<Vec<String>, String> {
+    let code_list: serde_json::Value =
+        serde_json::from_str(file_content).map_err(|err| err.to_string())?;
+    let codes = match &code_list {
+        serde_json::Value::Array(codes) => codes,
+        serde_json::Value::Object(code_list) => match code_list.get("codes") {
+            Some(serde_json::Value::Array(codes)) => codes,
+            _ => return Ok(code_list.keys().cloned().collect()),
+        },
+        _ => return Err("Code list must be an array or an object".to_string()),
+    };
+    Ok(codes
+        .iter()
+        .map(|code| match code {
+            serde_json::Value::String(code) => code.clone(),
+            code => code.to_string(),
+        })
+        .collect())
+}
+
+fn load_oca(
+    resolved_file: &ResolvedFile,
+    path: &str,
+    capture_base_said: &str,
+    overlay_saids: &[String],
+) -> Result<OCA, String> {
+    let file_content = |said: &str| {
+        resolved_file.files.get(said).cloned().ok_or(format!(
+            "Malformed OCA Bundle ({path}). Missing {said}.json file."
+        ))
+    };
+    let data = format!(
+        r#"{{"capture_base": {}, "overlays": [{}] }}"#,
+        file_content(capture_base_said)?,
+        overlay_saids
+            .iter()
+            .map(|said| file_content(said))
+            .collect::<Result<Vec<String>, String>>()?
+            .join(",")
+    );
+
+    let oca_builder = oca_rs::controller::load_oca(&mut data.as_bytes())
+        .map_err(|e| format!("Malformed OCA Bundle ({path}). {e:?}"))?;
+    Ok(oca_builder.finalize())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,4 +282,36 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn resolve_from_bytes_of_oca_bundle_is_ok() {
+        let path = format!("{}/oca_bundle_with_dir.zip", assets_dir_path());
+        let bytes = fs::read(path).unwrap();
+        assert!(resolve_bundle_from_bytes(&bytes, "upload").is_ok());
+
+        let bundle_result = resolve_bundle_from_bytes(b"not a zip", "upload");
+        assert!(bundle_result.is_err());
+    }
+
+    #[test]
+    fn resolve_oca_bundle_with_references_is_ok() {
+        let common_assets_dir_path = format!("{}/../assets", env!("CARGO_MANIFEST_DIR"));
+        let path = format!("{common_assets_dir_path}/oca_bundle_with_references.zip");
+        let bundle_result = resolve_bundle_from_zip(path.as_str());
+        assert!(bundle_result.is_ok());
+
+        let bundle = bundle_result.unwrap();
+        assert_eq!(bundle.oca.capture_base.attributes.len(), 4);
+        assert_eq!(bundle.references.len(), 1);
+        let (said, reference) = bundle.references.iter().next().unwrap();
+        assert_eq!(
+            bundle.oca.capture_base.attributes.get("subject"),
+            Some(&format!("Reference:{said}"))
+        );
+        assert_eq!(reference.overlays.len(), 4);
+        assert_eq!(
+            bundle.code_lists.values().collect::<Vec<_>>(),
+            vec![&vec!["glucose".to_string(), "cholesterol".to_string()]]
+        );
+    }
 }